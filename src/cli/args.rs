@@ -19,18 +19,505 @@ pub enum JournalCommand {
     /// Initialise your journal
     Init,
 
-    /// Creates a new journal entry for today
-    New,
+    /// Creates a new journal entry.  Defaults to right now
+    New(NewArgs),
 
-    /// Open today's entry
+    /// Open an entry in your editor.  Defaults to today's entry
     Open(OpenArgs),
-    // /// Prints a file to stdout. Default to today's entry.
-    // Show,
+
+    /// Prints entries to stdout.  Defaults to today's entries
+    Show(ShowArgs),
+
+    /// Lists entries, newest first
+    List(ListArgs),
+
+    /// Moves an entry to the trash.  Defaults to today's entry
+    Delete(DeleteArgs),
+
+    /// Lists, restores, or empties deleted entries
+    Trash(TrashArgs),
+
+    /// Searches the text of all entries
+    Search(SearchArgs),
+
+    /// Manages your saved places, e.g. home, office
+    Places(PlacesArgs),
+
+    /// Rebuilds the journal's search index from scratch
+    Reindex,
+
+    /// Checks your config file, templates and prompts
+    Config(ConfigArgs),
+
+    /// Lists the tags used in your entries, or renames one
+    Tags(TagsArgs),
+
+    /// Lists the people you mention with '@name', or what you wrote about one
+    People(PeopleArgs),
+
+    /// Shows how your mood, energy and sleep change, and how they follow the weather
+    Trends(TrendsArgs),
+
+    /// Shows your habits' streaks and completion rates, and a month's checklists as a grid
+    Habits(HabitsArgs),
+
+    /// Shows how much, when and where you have written
+    Stats(StatsArgs),
+
+    /// Shows a year or month of your journal as a heatmap, and opens the day you pick
+    Calendar(CalendarArgs),
+
+    /// Shows what you wrote on this day in earlier years
+    #[command(name = "onthisday")]
+    OnThisDay(OnThisDayArgs),
+
+    /// Writes a review of the week, month or year, starting from a draft of its entries
+    Review(ReviewArgs),
+
+    /// Encrypts or decrypts every entry of your journal
+    Encryption(EncryptionArgs),
+
+    /// Keeps your encrypted journal unlocked for a while, so you are not asked for the passphrase
+    Unlock(UnlockArgs),
+
+    /// Locks your encrypted journal again before `unlock` times out
+    Lock,
+
+    /// Lists the revisions of an entry, or of your whole journal, and shows what they changed
+    History(HistoryArgs),
+
+    /// Holds the key of an unlocked journal.  Started by `unlock`
+    #[command(hide = true)]
+    Agent(AgentArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct NewArgs {
+    /// Write the entry for another date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub date: Option<String>,
+
+    /// Write the entry for another time of day, in 'HH:MM' format.  Defaults to the current time
+    #[arg(short, long)]
+    pub time: Option<String>,
+
+    /// Write the entry from somewhere other than your usual location
+    #[arg(short, long, value_name = "QUERY", conflicts_with_all = ["recent", "at"])]
+    pub location: Option<String>,
+
+    /// Write the entry from one of your saved places (see `places`)
+    #[arg(short, long, value_name = "ALIAS", conflicts_with = "recent")]
+    pub at: Option<String>,
+
+    /// Write the entry from one of the locations you recently used with `--location`
+    #[arg(short, long)]
+    pub recent: bool,
+
+    /// Start the entry with the template 'templates/NAME.txt' in the config directory
+    #[arg(long, value_name = "NAME")]
+    pub template: Option<String>,
 }
 
 #[derive(Debug, Args)]
 pub struct OpenArgs {
     /// The date of the entry you want to open in 'YYYY-MM-DD' format
+    #[arg(short, long, conflicts_with = "latest")]
+    pub date: Option<String>,
+
+    /// Open the most recent entry in the journal
+    #[arg(short, long)]
+    pub latest: bool,
+
+    /// Open the N-th entry of that date (counting from 1) without being asked
+    #[arg(short, long)]
+    pub index: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Only list this many of the most recent entries
+    #[arg(value_name = "N")]
+    pub count: Option<usize>,
+
+    /// Only list entries on or after this date, in 'YYYY-MM-DD' format
     #[arg(short, long)]
+    pub from: Option<String>,
+
+    /// Only list entries on or before this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub to: Option<String>,
+
+    /// Only list this many of the most recent entries
+    #[arg(short, long, value_name = "N", conflicts_with = "count")]
+    pub last: Option<usize>,
+
+    /// List entries oldest first
+    #[arg(short, long)]
+    pub reverse: bool,
+
+    /// Only list entries written with this template (see `new --template`)
+    #[arg(long, value_name = "NAME")]
+    pub template: Option<String>,
+
+    /// Only list entries with this tag, e.g. 'work' for '#work'
+    #[arg(long)]
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ShowArgs {
+    /// The date of the entries you want to print in 'YYYY-MM-DD' format
+    #[arg(short, long, conflicts_with_all = ["from", "to", "latest"])]
     pub date: Option<String>,
-}
\ No newline at end of file
+
+    /// Print entries on or after this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub from: Option<String>,
+
+    /// Print entries on or before this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub to: Option<String>,
+
+    /// Print the most recent entry in the journal
+    #[arg(short, long, conflicts_with_all = ["from", "to"])]
+    pub latest: bool,
+
+    /// Only print the N-th entry of that date (counting from 1)
+    #[arg(short, long, conflicts_with_all = ["from", "to", "latest"])]
+    pub index: Option<usize>,
+
+    /// Print directly to stdout even when it is a terminal
+    #[arg(long)]
+    pub no_pager: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// The text to look for
+    pub query: String,
+
+    /// Treat the query as a regular expression
+    #[arg(short, long)]
+    pub regex: bool,
+
+    /// Match regardless of letter case
+    #[arg(short, long)]
+    pub ignore_case: bool,
+
+    /// Print this many lines before and after each match
+    #[arg(short = 'C', long, value_name = "N", default_value_t = 0)]
+    pub context: usize,
+
+    /// Only search entries on or after this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub from: Option<String>,
+
+    /// Only search entries on or before this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub to: Option<String>,
+
+    /// Only search entries written with this template (see `new --template`)
+    #[arg(long, value_name = "NAME")]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteArgs {
+    /// The date of the entry you want to delete in 'YYYY-MM-DD' format
+    #[arg(short, long, conflicts_with = "latest")]
+    pub date: Option<String>,
+
+    /// Delete the most recent entry in the journal
+    #[arg(short, long)]
+    pub latest: bool,
+
+    /// Delete the N-th entry of that date (counting from 1) without being asked
+    #[arg(short, long)]
+    pub index: Option<usize>,
+
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TrashArgs {
+    #[clap(subcommand)]
+    pub trash_command: TrashCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TrashCommand {
+    /// Lists deleted entries
+    List,
+
+    /// Moves a deleted entry back into the journal
+    Restore(TrashRestoreArgs),
+
+    /// Permanently removes deleted entries
+    Empty(TrashEmptyArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct TrashRestoreArgs {
+    /// The date of the deleted entry in 'YYYY-MM-DD' format.  Defaults to the most recently deleted entry
+    #[arg(short, long)]
+    pub date: Option<String>,
+
+    /// Restore the N-th deleted entry of that date (counting from 1) without being asked
+    #[arg(short, long, requires = "date")]
+    pub index: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct TrashEmptyArgs {
+    /// Only remove entries deleted longer ago than this, e.g. '30d', '2w', '12h'
+    #[arg(long, value_name = "AGE")]
+    pub older_than: Option<String>,
+
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PlacesArgs {
+    #[clap(subcommand)]
+    pub places_command: PlacesCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PlacesCommand {
+    /// Saves a new place under an alias
+    Add {
+        /// The name you will use for this place, e.g. 'office'
+        alias: String,
+    },
+
+    /// Forgets a saved place
+    Remove {
+        /// The alias of the place
+        alias: String,
+    },
+
+    /// Lists your saved places
+    List,
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigArgs {
+    #[clap(subcommand)]
+    pub config_command: ConfigCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Checks that your config file, templates and prompts can be read
+    Check,
+}
+
+#[derive(Debug, Args)]
+pub struct TagsArgs {
+    #[clap(subcommand)]
+    pub tags_command: Option<TagsCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TagsCommand {
+    /// Lists every tag with how many entries use it, and when it was first and last used.  The default
+    List,
+
+    /// Renames a tag in every entry.  Renaming to a tag that is already used merges the two
+    Rename(TagsRenameArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct TagsRenameArgs {
+    /// The tag to rename, e.g. 'work' for '#work'
+    pub old: String,
+
+    /// The new name of the tag
+    pub new: String,
+
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PeopleArgs {
+    #[clap(subcommand)]
+    pub people_command: Option<PeopleCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PeopleCommand {
+    /// Lists everyone you mentioned, with how often and when you last did.  The default
+    List,
+
+    /// Prints every line that mentions someone, oldest first
+    Show(PeopleShowArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct PeopleShowArgs {
+    /// The person's name or one of their aliases, e.g. 'alice' for '@alice'
+    pub name: String,
+
+    /// Print directly to stdout even when it is a terminal
+    #[arg(long)]
+    pub no_pager: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TrendsArgs {
+    /// Average over weeks.  The default
+    #[arg(short, long, conflicts_with = "monthly")]
+    pub weekly: bool,
+
+    /// Average over months
+    #[arg(short, long)]
+    pub monthly: bool,
+
+    /// Only use entries on or after this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub from: Option<String>,
+
+    /// Only use entries on or before this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct HabitsArgs {
+    /// The month to show as a grid, in 'YYYY-MM' format.  Defaults to the current month
+    #[arg(short, long)]
+    pub month: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    /// Only count entries on or after this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub from: Option<String>,
+
+    /// Only count entries on or before this date, in 'YYYY-MM-DD' format
+    #[arg(short, long)]
+    pub to: Option<String>,
+
+    /// Print the statistics as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CalendarArgs {
+    /// The year ('YYYY') or month ('YYYY-MM') to show.  Defaults to the current year
+    pub period: Option<String>,
+
+    /// Shade days by the number of words written instead of the number of entries
+    #[arg(short, long)]
+    pub words: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct OnThisDayArgs {
+    /// Look back on another day of the year, in 'MM-DD' format.  Defaults to today
+    #[arg(short, long)]
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReviewArgs {
+    /// Review a week, from Monday to Sunday
+    #[arg(short, long, conflicts_with_all = ["month", "year"])]
+    pub week: bool,
+
+    /// Review a month.  The default
+    #[arg(short, long, conflicts_with = "year")]
+    pub month: bool,
+
+    /// Review a year
+    #[arg(short, long)]
+    pub year: bool,
+
+    /// Review the period that holds this date, in 'YYYY-MM-DD' format.  Defaults to today
+    #[arg(short, long)]
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct EncryptionArgs {
+    #[clap(subcommand)]
+    pub encryption_command: EncryptionCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EncryptionCommand {
+    /// Encrypts your entries, reviews and trash with a passphrase or your key file
+    Enable,
+
+    /// Decrypts every file of your journal and stops encrypting new ones
+    Disable,
+}
+
+#[derive(Debug, Args)]
+pub struct UnlockArgs {
+    /// How long to keep the journal unlocked, e.g. '15m', '2h'
+    #[arg(short, long, default_value = "15m")]
+    pub timeout: String,
+}
+
+#[derive(Debug, Args)]
+pub struct AgentArgs {
+    /// How many seconds to hold the key for
+    #[arg(long)]
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct HistoryArgs {
+    #[clap(subcommand)]
+    pub history_command: Option<HistoryCommand>,
+
+    #[command(flatten)]
+    pub entry: HistoryEntryArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// Lists the revisions of an entry, or of your whole journal if none is given.  The default
+    List(HistoryEntryArgs),
+
+    /// Shows what a revision changed, in an entry or in your whole journal
+    Diff(HistoryDiffArgs),
+
+    /// Starts keeping your journal's history with git
+    Enable,
+}
+
+#[derive(Debug, Args)]
+pub struct HistoryEntryArgs {
+    /// The date of the entry in 'YYYY-MM-DD' format
+    #[arg(short, long, conflicts_with = "latest")]
+    pub date: Option<String>,
+
+    /// The most recent entry in the journal
+    #[arg(short, long)]
+    pub latest: bool,
+
+    /// The N-th entry of that date (counting from 1), without being asked
+    #[arg(short, long)]
+    pub index: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+pub struct HistoryDiffArgs {
+    #[command(flatten)]
+    pub entry: HistoryEntryArgs,
+
+    /// The revision to show, as listed by `history`.  Defaults to the most recent one
+    #[arg(short, long)]
+    pub revision: Option<String>,
+}
@@ -46,6 +46,15 @@ pub(crate) fn ask_user_for_location(
         "[optional address specifiers], <location>",
     )?;
 
+    ask_user_to_confirm_location(full_address)
+}
+
+/// Looks up `full_address` and asks the user which of the results they meant.
+///
+/// As with `ask_user_for_location`, only the last part of `full_address` is looked up.
+pub(crate) fn ask_user_to_confirm_location(
+    full_address: String,
+) -> Result<(String, journey2::core::Location), journey2::cli::interaction::InteractionError> {
     let city = journey2::core::str_man::sanitise_spaces_html(
         journey2::core::str_man::split_location(&full_address),
     );
@@ -66,6 +75,76 @@ pub(crate) fn ask_user_for_location(
     Ok((full_address, city_info.clone()))
 }
 
+pub(crate) fn ask_user_for_place_location(
+    alias: &str,
+) -> Result<(String, journey2::core::Location), journey2::cli::interaction::InteractionError> {
+    let full_address: String = super::q_basic::prompt_user_for_string(
+        &format!("Where is '{}'?", alias),
+        "[optional address specifiers], <location>",
+    )?;
+
+    ask_user_to_confirm_location(full_address)
+}
+
+pub(crate) fn ask_if_to_overwrite_place(alias: &str) -> std::io::Result<bool> {
+    if !super::q_basic::prompt_user_for_bool(&format!(
+        "There is already a place called '{}'.  Would you like to replace it?",
+        alias
+    ))? {
+        println!("Adding the place cancelled.");
+
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn ask_for_recent_place(
+    recent_places: &[journey2::core::Place],
+) -> Result<journey2::core::Place, journey2::cli::interaction::InteractionError> {
+    let place = super::q_basic::prompt_user_for_choice(
+        "You haven't written from any other location yet.  Try `new --location` first.",
+        "Where are you writing from?",
+        "Enter the number of the location",
+        "That doesn't seem to be one of the choices.",
+        "Too many failed inputs.",
+        recent_places,
+    )?;
+
+    Ok(place.clone())
+}
+
+/// Asks for the values turned on in the `[tracking]` table of the config file
+pub(crate) fn ask_for_tracking(
+    conf_tracking: &journey2::core::file::ConfTracking,
+) -> Result<journey2::core::tracking::Tracking, journey2::cli::interaction::InteractionError> {
+    let mut tracking = journey2::core::tracking::Tracking::default();
+
+    if conf_tracking.mood {
+        tracking.mood = super::q_basic::prompt_user_for_optional_number(
+            "How is your mood?  (1 is awful, 5 is great)",
+            1.0,
+            5.0,
+        )?;
+    }
+    if conf_tracking.energy {
+        tracking.energy = super::q_basic::prompt_user_for_optional_number(
+            "How is your energy?  (1 is drained, 5 is full of it)",
+            1.0,
+            5.0,
+        )?;
+    }
+    if conf_tracking.sleep {
+        tracking.sleep = super::q_basic::prompt_user_for_optional_number(
+            "How many hours did you sleep?",
+            0.0,
+            24.0,
+        )?;
+    }
+
+    Ok(tracking)
+}
+
 pub(crate) fn ask_for_editor() -> Result<String, journey2::cli::interaction::InteractionError> {
     let choice = super::q_basic::prompt_user_for_usize(
         r#"Which text editor would you like to use?  (You have to install this separately.)
@@ -117,3 +196,101 @@ pub(crate) fn ask_if_to_overwrite_config() -> std::io::Result<bool> {
 
     Ok(true)
 }
+
+pub(crate) fn ask_if_to_keep_history() -> std::io::Result<bool> {
+    super::q_basic::prompt_user_for_bool(
+        "Keep a history of every change to your entries with git, so that none is ever lost?",
+    )
+}
+
+/// Asks before encrypting a journal with a history, which holds the unencrypted versions of every entry
+pub(crate) fn ask_if_to_restart_history() -> std::io::Result<bool> {
+    println!("Warning: your journal's history keeps every earlier version of your entries, unencrypted.  Encrypting the journal does not change them.");
+    if !super::q_basic::prompt_user_for_bool(
+        "Delete the history, and start it over from the encrypted journal?",
+    )? {
+        println!("Encryption cancelled.  Your journal is not encrypted.");
+
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn ask_if_to_delete_entry(datetime: &chrono::NaiveDateTime) -> std::io::Result<bool> {
+    if !super::q_basic::prompt_user_for_bool(&format!(
+        "Move the entry from {} to the trash?",
+        datetime.format("%d %B %Y, %H:%M")
+    ))? {
+        println!("Deletion cancelled.");
+
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn ask_if_to_empty_trash(count: usize) -> std::io::Result<bool> {
+    if !super::q_basic::prompt_user_for_bool(&format!(
+        "Permanently remove {} entries from the trash?  This cannot be undone.",
+        count
+    ))? {
+        println!("Emptying the trash cancelled.");
+
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn ask_if_to_rename_tag(
+    old: &str,
+    new: &str,
+    count: usize,
+    is_merge: bool,
+) -> std::io::Result<bool> {
+    let question = if is_merge {
+        format!(
+            "Merge #{} into the existing #{} in {} entries?",
+            old, new, count
+        )
+    } else {
+        format!("Rename #{} to #{} in {} entries?", old, new, count)
+    };
+
+    if !super::q_basic::prompt_user_for_bool(&question)? {
+        println!("Renaming the tag cancelled.");
+
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+pub(crate) fn ask_for_passphrase() -> std::io::Result<zeroize::Zeroizing<String>> {
+    super::q_basic::prompt_user_for_secret("Passphrase of your journal")
+}
+
+/// Asks for a new passphrase twice, so that a typo does not lock the user out
+pub(crate) fn ask_for_new_passphrase(
+) -> Result<zeroize::Zeroizing<String>, journey2::cli::interaction::InteractionError> {
+    let mut chances = 3;
+    while chances > 0 {
+        let passphrase = super::q_basic::prompt_user_for_secret("New passphrase")?;
+        if passphrase.is_empty() {
+            println!("The passphrase cannot be empty.");
+        } else if *super::q_basic::prompt_user_for_secret("Enter it again")? == *passphrase {
+            return Ok(passphrase);
+        } else {
+            println!("The passphrases do not match.");
+        }
+        chances -= 1;
+    }
+
+    Err(journey2::cli::interaction::InteractionError::from(
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Too many failed attempts.",
+        ),
+    ))
+}
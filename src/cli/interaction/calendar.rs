@@ -0,0 +1,330 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Draws the heatmap of `calendar`, and lets the user move around it to pick a day
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use chrono::Datelike;
+use crossterm::style::Stylize;
+
+use super::InteractionError;
+use crate::core::calendar::{CalendarPeriod, DayTotals, Shading, LEVEL_COUNT};
+
+/// Shades used when the output cannot be coloured, from no entries to the most
+const LEVEL_GLYPHS: [char; LEVEL_COUNT] = ['·', '░', '▒', '▓', '█'];
+
+/// 256-colour palette greens, from no entries to the most
+const LEVEL_COLOURS: [u8; LEVEL_COUNT] = [238, 22, 28, 34, 40];
+
+/// Every day of the period as the heatmap shows it
+struct Heatmap<'a> {
+    period: CalendarPeriod,
+    days: &'a BTreeMap<chrono::NaiveDate, DayTotals>,
+    shading: Shading,
+    /// The highest value of the period, which gets the darkest shade
+    max: usize,
+}
+
+impl<'a> Heatmap<'a> {
+    fn new(
+        period: CalendarPeriod,
+        days: &'a BTreeMap<chrono::NaiveDate, DayTotals>,
+        shading: Shading,
+    ) -> Self {
+        let max = days
+            .range(period.get_first_day()..=period.get_last_day())
+            .map(|(_, totals)| totals.get(shading))
+            .max()
+            .unwrap_or(0);
+
+        Heatmap {
+            period,
+            days,
+            shading,
+            max,
+        }
+    }
+
+    fn get_level(&self, day: chrono::NaiveDate) -> usize {
+        let value = self
+            .days
+            .get(&day)
+            .map_or(0, |totals| totals.get(self.shading));
+
+        crate::core::calendar::get_level(value, self.max)
+    }
+
+    /// Draws `text` in the day's shade, highlighted if the cursor is on it
+    fn style(&self, text: String, level: usize, is_cursor: bool) -> String {
+        let styled = text.with(crossterm::style::Color::AnsiValue(LEVEL_COLOURS[level]));
+
+        if is_cursor {
+            styled.reverse().to_string()
+        } else {
+            styled.to_string()
+        }
+    }
+
+    /// A column per week and a row per weekday, as on GitHub
+    fn render_year(&self, cursor: Option<chrono::NaiveDate>, is_styled: bool) -> Vec<String> {
+        const LABEL_WIDTH: usize = 4;
+        const WEEKDAY_LABELS: [&str; 7] = ["Mon", "", "Wed", "", "Fri", "", "Sun"];
+
+        let first_day = self.period.get_first_day();
+        let last_day = self.period.get_last_day();
+        let first_monday =
+            first_day - chrono::Duration::days(first_day.weekday().num_days_from_monday() as i64);
+        let week_count = (last_day - first_monday).num_days() as usize / 7 + 1;
+
+        // A month's name goes above the week of its first day, if there is room
+        let mut month_labels = vec![' '; LABEL_WIDTH + week_count * 2];
+        let mut label_end = 0;
+        for month in 1..=12 {
+            let Some(month_start) = first_day.with_month(month) else {
+                continue;
+            };
+            let position = LABEL_WIDTH + (month_start - first_monday).num_days() as usize / 7 * 2;
+            if position < label_end {
+                continue;
+            }
+            for (i, c) in month_start.format("%b").to_string().chars().enumerate() {
+                if let Some(slot) = month_labels.get_mut(position + i) {
+                    *slot = c;
+                }
+            }
+            label_end = position + 4;
+        }
+
+        let mut lines = vec![
+            format!("{}", first_day.year()),
+            month_labels
+                .into_iter()
+                .collect::<String>()
+                .trim_end()
+                .to_string(),
+        ];
+        for (weekday, label) in WEEKDAY_LABELS.iter().enumerate() {
+            let mut line = format!("{:<width$}", label, width = LABEL_WIDTH);
+            for week in 0..week_count {
+                let day = first_monday + chrono::Duration::days((week * 7 + weekday) as i64);
+                if day < first_day || day > last_day {
+                    line.push_str("  ");
+                    continue;
+                }
+                let level = self.get_level(day);
+                if is_styled {
+                    line.push_str(&self.style("■".to_string(), level, cursor == Some(day)));
+                } else {
+                    line.push(LEVEL_GLYPHS[level]);
+                }
+                line.push(' ');
+            }
+            lines.push(line.trim_end().to_string());
+        }
+
+        lines
+    }
+
+    /// A row per week, with each day's number
+    fn render_month(&self, cursor: Option<chrono::NaiveDate>, is_styled: bool) -> Vec<String> {
+        let first_day = self.period.get_first_day();
+
+        let mut lines = vec![
+            first_day.format("%B %Y").to_string(),
+            " Mo  Tu  We  Th  Fr  Sa  Su".to_string(),
+        ];
+        let mut line = "    ".repeat(first_day.weekday().num_days_from_monday() as usize);
+        for day in self.period.get_days() {
+            let level = self.get_level(day);
+            if is_styled {
+                line.push_str(&self.style(format!("{:>3}", day.day()), level, cursor == Some(day)));
+                line.push(' ');
+            } else {
+                line.push_str(&format!("{:>2}{} ", day.day(), LEVEL_GLYPHS[level]));
+            }
+            if day.weekday() == chrono::Weekday::Sun {
+                lines.push(line.trim_end().to_string());
+                line = String::new();
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.trim_end().to_string());
+        }
+
+        lines
+    }
+
+    fn render(&self, cursor: Option<chrono::NaiveDate>, is_styled: bool) -> Vec<String> {
+        let mut lines = match self.period {
+            CalendarPeriod::Year(_) => self.render_year(cursor, is_styled),
+            CalendarPeriod::Month(_) => self.render_month(cursor, is_styled),
+        };
+
+        let legend: Vec<String> = (0..LEVEL_COUNT)
+            .map(|level| {
+                if is_styled {
+                    self.style("■".to_string(), level, false)
+                } else {
+                    LEVEL_GLYPHS[level].to_string()
+                }
+            })
+            .collect();
+        let unit = match self.shading {
+            Shading::EntryCount => "entries",
+            Shading::WordCount => "words",
+        };
+        lines.push(String::new());
+        lines.push(format!("Fewer {} {} more {}", unit, legend.join(" "), unit));
+
+        lines
+    }
+}
+
+/// Returns the heatmap of `period` as lines of text, coloured if `is_styled`
+pub(in crate::cli) fn render_calendar(
+    period: CalendarPeriod,
+    days: &BTreeMap<chrono::NaiveDate, DayTotals>,
+    shading: Shading,
+    is_styled: bool,
+) -> Vec<String> {
+    Heatmap::new(period, days, shading).render(None, is_styled)
+}
+
+/// Puts the terminal back the way it was, even if drawing fails
+struct RawScreen;
+
+impl RawScreen {
+    fn enter() -> std::io::Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        crossterm::execute!(
+            std::io::stdout(),
+            crossterm::terminal::EnterAlternateScreen,
+            crossterm::cursor::Hide
+        )?;
+
+        Ok(RawScreen)
+    }
+}
+
+impl Drop for RawScreen {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(
+            std::io::stdout(),
+            crossterm::cursor::Show,
+            crossterm::terminal::LeaveAlternateScreen
+        );
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+/// Shows the heatmap and lets the user move between days with the arrow keys.
+///
+/// # Arguments
+///
+/// * `period` - The year or month to show first.  Moving past its ends shows the next or previous one.
+/// * `cursor` - The day to start on.  Defaults to the period's last day with an entry.
+///
+/// # Returns
+///
+/// The day the user pressed enter on, or `None` if they quit.  Only days with entries can be picked.
+pub(in crate::cli) fn ask_for_calendar_day(
+    period: CalendarPeriod,
+    cursor: Option<chrono::NaiveDate>,
+    days: &BTreeMap<chrono::NaiveDate, DayTotals>,
+    shading: Shading,
+) -> Result<Option<chrono::NaiveDate>, InteractionError> {
+    use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+
+    let mut period = period;
+    let mut cursor = cursor
+        .map(|cursor| period.clamp(cursor))
+        .unwrap_or_else(|| {
+            days.range(period.get_first_day()..=period.get_last_day())
+                .next_back()
+                .map_or(period.get_first_day(), |(day, _)| *day)
+        });
+    let mut message = String::new();
+
+    let _screen = RawScreen::enter()?;
+    let mut stdout = std::io::stdout();
+
+    loop {
+        let mut lines = Heatmap::new(period, days, shading).render(Some(cursor), true);
+        lines.push(String::new());
+        lines.push(match days.get(&cursor) {
+            Some(totals) => format!(
+                "{}: {} {}, {} words",
+                cursor.format("%a, %d %b %Y"),
+                totals.entry_count,
+                if totals.entry_count == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                },
+                totals.word_count
+            ),
+            None => format!("{}: no entries", cursor.format("%a, %d %b %Y")),
+        });
+        lines.push(message.clone());
+        lines.push(
+            "Arrows: move  n/p: next/previous day with entries  Enter: open  q: quit"
+                .dark_grey()
+                .to_string(),
+        );
+
+        crossterm::queue!(
+            stdout,
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::All)
+        )?;
+        for (row, line) in lines.iter().enumerate() {
+            crossterm::queue!(
+                stdout,
+                crossterm::cursor::MoveTo(0, row as u16),
+                crossterm::style::Print(line)
+            )?;
+        }
+        stdout.flush()?;
+
+        let Event::Key(key) = crossterm::event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        message.clear();
+
+        // Across a year, a column is a week.  Across a month, a row is.
+        let (horizontal_step, vertical_step) = match period {
+            CalendarPeriod::Year(_) => (7, 1),
+            CalendarPeriod::Month(_) => (1, 7),
+        };
+        let moved_cursor = match key.code {
+            KeyCode::Left => cursor.checked_sub_days(chrono::Days::new(horizontal_step)),
+            KeyCode::Right => cursor.checked_add_days(chrono::Days::new(horizontal_step)),
+            KeyCode::Up => cursor.checked_sub_days(chrono::Days::new(vertical_step)),
+            KeyCode::Down => cursor.checked_add_days(chrono::Days::new(vertical_step)),
+            KeyCode::Char('n') => days
+                .range(cursor.succ_opt().unwrap_or(cursor)..)
+                .next()
+                .map(|(day, _)| *day),
+            KeyCode::Char('p') => days.range(..cursor).next_back().map(|(day, _)| *day),
+            KeyCode::Enter if days.contains_key(&cursor) => return Ok(Some(cursor)),
+            KeyCode::Enter => {
+                message = "There are no entries on that day.".to_string();
+                None
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            _ => None,
+        };
+
+        if let Some(moved_cursor) = moved_cursor {
+            cursor = moved_cursor;
+            period = period.get_period_of(cursor);
+        }
+    }
+}
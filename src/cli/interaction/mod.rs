@@ -5,6 +5,7 @@
 
 
 pub(in crate::cli) mod ask;
+pub(in crate::cli) mod calendar;
 pub(in crate::cli) mod q_basic;
 
 use crate::core;
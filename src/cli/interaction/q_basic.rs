@@ -48,6 +48,18 @@ pub(super) fn prompt_user_for_string(
     Ok(user_response.trim_end().to_string())
 }
 
+/// Prints a prompt and reads the response without echoing it, e.g. for passphrases.
+///
+/// The response is wiped from memory when dropped.
+pub(super) fn prompt_user_for_secret(
+    question: &str,
+) -> std::io::Result<zeroize::Zeroizing<String>> {
+    println!();
+    let user_response = rpassword::prompt_password(format!("{question}: "))?;
+
+    Ok(zeroize::Zeroizing::new(user_response))
+}
+
 pub(super) fn prompt_user_for_choice_index<'a, T>(
     msg_when_none: &'a str,
     msg_when_many: &'a str,
@@ -128,6 +140,35 @@ where
     }
 }
 
+/// Prints a prompt and parses the response as a number from `min` to `max`.
+///
+/// An empty response skips the question, in which case `None` is returned.
+pub(super) fn prompt_user_for_optional_number(
+    question: &str,
+    min: f64,
+    max: f64,
+) -> Result<Option<f64>, super::InteractionError> {
+    let mut chances = 5;
+    while chances > 0 {
+        let user_response =
+            prompt_user_for_string(question, &format!("{} to {}, or empty to skip", min, max))?;
+
+        if user_response.trim().is_empty() {
+            return Ok(None);
+        }
+        match user_response.trim().parse::<f64>() {
+            Ok(number) if (min..=max).contains(&number) => return Ok(Some(number)),
+            _ => println!("That should be a number from {} to {}.", min, max),
+        }
+        chances -= 1;
+    }
+
+    Err(super::InteractionError::from(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "Too many failed inputs.",
+    )))
+}
+
 pub(in super) fn prompt_user_for_usize(
     question: &str,
 ) -> Result<usize, super::InteractionError> {
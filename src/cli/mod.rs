@@ -10,20 +10,74 @@ mod utils {
     pub(crate) mod functions;
 }
 
-use std::io::{Read, Write};
-
 use crate as journey2;
-use crate::cli::args::OpenArgs;
+use crate::cli::args::{
+    AgentArgs, CalendarArgs, ConfigCommand, DeleteArgs, EncryptionCommand, HabitsArgs,
+    HistoryCommand, HistoryDiffArgs, HistoryEntryArgs, ListArgs, NewArgs, OnThisDayArgs, OpenArgs,
+    PeopleCommand, PeopleShowArgs, PlacesCommand, ReviewArgs, SearchArgs, ShowArgs, StatsArgs,
+    TagsCommand, TagsRenameArgs, TrashCommand, TrashEmptyArgs, TrashRestoreArgs, TrendsArgs,
+    UnlockArgs,
+};
 
-/** Calls the appropriate function for each subcommand (`init`, `new`, `open`)
+/** Calls the appropriate function for each subcommand (`init`, `new`, `open`, ...)
  */
 pub fn handle_main() -> Result<(), Box<dyn std::error::Error>> {
     let args = <args::JournalArgs as clap::Parser>::parse();
+    journey2::core::crypto::set_secret_provider(utils::functions::get_journal_secret);
     if let Some(command) = args.journal_command {
         match command {
             args::JournalCommand::Init => handle_init()?,
-            args::JournalCommand::New => handle_new()?,
+            args::JournalCommand::New(new_args) => handle_new(new_args)?,
             args::JournalCommand::Open(open_args) => handle_open(open_args)?,
+            args::JournalCommand::Show(show_args) => handle_show(show_args)?,
+            args::JournalCommand::List(list_args) => handle_list(list_args)?,
+            args::JournalCommand::Delete(delete_args) => handle_delete(delete_args)?,
+            args::JournalCommand::Trash(trash_args) => match trash_args.trash_command {
+                TrashCommand::List => handle_trash_list()?,
+                TrashCommand::Restore(restore_args) => handle_trash_restore(restore_args)?,
+                TrashCommand::Empty(empty_args) => handle_trash_empty(empty_args)?,
+            },
+            args::JournalCommand::Search(search_args) => handle_search(search_args)?,
+            args::JournalCommand::Reindex => handle_reindex()?,
+            args::JournalCommand::Places(places_args) => match places_args.places_command {
+                PlacesCommand::Add { alias } => handle_places_add(alias)?,
+                PlacesCommand::Remove { alias } => handle_places_remove(alias)?,
+                PlacesCommand::List => handle_places_list()?,
+            },
+            args::JournalCommand::Config(config_args) => match config_args.config_command {
+                ConfigCommand::Check => handle_config_check()?,
+            },
+            args::JournalCommand::Tags(tags_args) => match tags_args.tags_command {
+                None | Some(TagsCommand::List) => handle_tags_list()?,
+                Some(TagsCommand::Rename(rename_args)) => handle_tags_rename(rename_args)?,
+            },
+            args::JournalCommand::People(people_args) => match people_args.people_command {
+                None | Some(PeopleCommand::List) => handle_people_list()?,
+                Some(PeopleCommand::Show(show_args)) => handle_people_show(show_args)?,
+            },
+            args::JournalCommand::Trends(trends_args) => handle_trends(trends_args)?,
+            args::JournalCommand::Habits(habits_args) => handle_habits(habits_args)?,
+            args::JournalCommand::Stats(stats_args) => handle_stats(stats_args)?,
+            args::JournalCommand::Calendar(calendar_args) => handle_calendar(calendar_args)?,
+            args::JournalCommand::OnThisDay(on_this_day_args) => {
+                handle_on_this_day(on_this_day_args)?
+            }
+            args::JournalCommand::Review(review_args) => handle_review(review_args)?,
+            args::JournalCommand::Encryption(encryption_args) => {
+                match encryption_args.encryption_command {
+                    EncryptionCommand::Enable => handle_encryption_enable()?,
+                    EncryptionCommand::Disable => handle_encryption_disable()?,
+                }
+            }
+            args::JournalCommand::Unlock(unlock_args) => handle_unlock(unlock_args)?,
+            args::JournalCommand::Lock => handle_lock()?,
+            args::JournalCommand::History(history_args) => match history_args.history_command {
+                None => handle_history_list(history_args.entry)?,
+                Some(HistoryCommand::List(entry_args)) => handle_history_list(entry_args)?,
+                Some(HistoryCommand::Diff(diff_args)) => handle_history_diff(diff_args)?,
+                Some(HistoryCommand::Enable) => handle_history_enable()?,
+            },
+            args::JournalCommand::Agent(agent_args) => handle_agent(agent_args)?,
         }
     }
     Ok(())
@@ -50,7 +104,9 @@ fn handle_init() -> Result<(), Box<dyn std::error::Error>> {
         location_latitude=\"{}\"\n\
         location_longitude=\"{}\"\n\
         timezone=\"{}\"\n\
-        editor=\"{}\"\n",
+        editor=\"{}\"\n\
+        entry_mode=\"per-moment\"\n\
+        entry_format=\"plain\"\n",
         default_location_string,
         default_location.latitude,
         default_location.longitude,
@@ -72,154 +128,1861 @@ fn handle_init() -> Result<(), Box<dyn std::error::Error>> {
 
     journey2::core::file::write_contents_to_config_file(config_contents)?;
 
+    if interaction::ask::ask_if_to_keep_history()? {
+        match journey2::core::history::enable_history() {
+            Ok(_) => println!("Your journal's history will be kept with git."),
+            Err(error) => println!(
+                "\nYour journal's history cannot be kept ({}).  Run `journey2 history enable` to try again.",
+                error
+            ),
+        }
+    }
+
+    // A template may be left over from an earlier installation
+    if let Err(error) = utils::functions::get_preamble_template() {
+        println!(
+            "\nYour preamble template has a problem, so `new` will not work until it is fixed:"
+        );
+        println!("    {}", error);
+    }
+
     Ok(())
 }
 
-fn handle_new() -> Result<(), Box<dyn std::error::Error>> {
+fn handle_new(args: NewArgs) -> Result<(), Box<dyn std::error::Error>> {
     if !utils::functions::is_journal_initialised_frontend()? {
         return Ok(()); // Early return if journal not initialised
     }
 
-    let config_data = journey2::core::file::get_config_from_config_file()?.defaults;
+    let config = journey2::core::file::get_config_from_config_file()?;
+    let config_data = &config.defaults;
+    // Read before anything else so that a broken template is reported straight away
+    let preamble_template = utils::functions::get_preamble_template()?;
+    let entry_template = args
+        .template
+        .as_deref()
+        .map(utils::functions::get_entry_template)
+        .transpose()?;
 
-    let current_date =
-        journey2::core::chrono::get_current_date_from_tz_as_str(&config_data.timezone)?;
+    // Entries written away from the usual location use that location's weather and time zone
+    let is_ad_hoc_place = args.location.is_some() || args.recent;
 
-    let current_weather = journey2::core::weather::query::get_current_weather_at_location_and_time(
-        &current_date.to_string(),
-        &config_data.location_latitude.to_string(),
-        &config_data.location_longitude.to_string(),
-        &config_data.timezone,
-    )?;
+    let place = if let Some(query) = args.location {
+        let (full_address, location) = interaction::ask::ask_user_to_confirm_location(query)?;
+        journey2::core::Place::from_location(full_address, &location)
+    } else if let Some(alias) = args.at {
+        config.places.get(&alias).cloned().ok_or_else(|| {
+            format!(
+                "There is no saved place called '{}'.  See `places list`.",
+                alias
+            )
+        })?
+    } else if args.recent {
+        interaction::ask::ask_for_recent_place(&journey2::core::file::get_recent_places()?)?
+    } else {
+        config_data.get_default_place()
+    };
+
+    let now = journey2::core::chrono::get_current_date_from_tz_as_str(&place.timezone)?;
+
+    // Backdated and future-dated entries default to the current time of day
+    let current_date = if args.date.is_some() || args.time.is_some() {
+        let date = match args.date {
+            Some(date) => utils::functions::parse_date_arg(&date)?,
+            None => now.date_naive(),
+        };
+        let time = match args.time {
+            Some(time) => utils::functions::parse_time_arg(&time)?,
+            None => now.time(),
+        };
+
+        journey2::core::chrono::get_date_from_tz_as_str(&place.timezone, date, time)?
+    } else {
+        now
+    };
+
+    // In per-day mode, the day's first entry is the only entry of the day
+    let todays_entry = match config_data.entry_mode {
+        journey2::core::file::EntryMode::PerDay => journey2::core::index::load_index()?
+            .get_entries()?
+            .into_iter()
+            .find(|entry| entry.datetime.date() == current_date.date_naive()),
+        journey2::core::file::EntryMode::PerMoment => None,
+    };
+
+    // Entries are filed by the minute, so another entry in the same minute would be lost
+    let is_new_entry = todays_entry.is_none();
+    let new_entry_path = journey2::core::file::get_path_for_entry(&current_date)?;
+    if is_new_entry && new_entry_path.exists() {
+        return Err(format!(
+            "There is already an entry at {}.  Open it with `journey2 open --date {}`, \
+            or give `new` another `--time`.",
+            current_date.format("%Y-%m-%d %H:%M"),
+            current_date.format("%Y-%m-%d")
+        )
+        .into());
+    }
 
-    let preamble_str = utils::functions::generate_preamble(
-        &config_data.location_full_name,
+    let current_weather = journey2::core::weather::query::get_weather_at_location_and_time(
         &current_date,
-        current_weather,
-    );
+        &place.latitude.to_string(),
+        &place.longitude.to_string(),
+        &place.timezone,
+    )?;
+    if current_weather.is_none() {
+        println!(
+            "There is no weather for {}, so the entry will be written without it.",
+            current_date.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    let (mut entry_path, initial_content) = match todays_entry {
+        Some(todays_entry) => {
+            let mut content = journey2::core::file::read_entry(&todays_entry.path)?;
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            let location_full_name = Some(place.full_name.as_str()).filter(|full_name| {
+                journey2::core::entry::get_header_field(&content, "LOCATION").as_deref()
+                    != Some(full_name)
+            });
+            content.push_str(&utils::functions::generate_sub_preamble(
+                &current_date,
+                location_full_name,
+                current_weather.as_ref(),
+                args.template.as_deref(),
+            ));
+            // The sub-preamble already ends with a blank line.  The habit checklist is
+            // already in the day's first section.
+            content.push_str(
+                utils::functions::generate_body_scaffold(None, entry_template.as_deref(), &[])
+                    .trim_start_matches('\n'),
+            );
 
-    print!("{}", preamble_str);
+            (todays_entry.path, content)
+        }
+        None => {
+            // Only asked for the day's first section in `per-day` mode, as they describe the day
+            let tracking = interaction::ask::ask_for_tracking(&config.tracking)?;
 
-    println!("{:?}", journey2::core::file::get_temp_file_path()?);
+            let mut fields: Vec<(String, String)> = args
+                .template
+                .iter()
+                .map(|name| {
+                    (
+                        journey2::core::entry::TEMPLATE_FIELD.to_string(),
+                        name.clone(),
+                    )
+                })
+                .collect();
+            fields.extend(tracking.to_fields());
 
-    // Write temporary file to data directory
-    let mut temporary_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open(journey2::core::file::get_temp_file_path()?)?;
+            let preamble_str = utils::functions::generate_preamble(
+                &place.full_name,
+                &current_date,
+                current_weather,
+                config_data.entry_format,
+                preamble_template.as_ref(),
+                fields,
+            );
 
-    std::io::Write::write_all(&mut temporary_file, preamble_str.as_bytes())?;
+            print!("{}", preamble_str);
 
-    // Invoke the editor as a subprocess
-    let editor_proc = std::process::Command::new(&config_data.editor)
-        .arg(&journey2::core::file::get_temp_file_path()?)
-        .stdin(std::process::Stdio::inherit())
-        .stdout(std::process::Stdio::inherit())
-        .stderr(std::process::Stdio::inherit())
-        .status()
-        .expect(&format!("Failed to start {}", &config_data.editor));
+            print_on_this_day_teaser(current_date.date_naive());
 
-    if !editor_proc.success() {
-        println!("Vim was not successful");
-        return Ok(());
-    }
+            // Only the day's first section gets a prompt, as it would be the same prompt again
+            let prompts = journey2::core::file::get_prompts()?;
+            let prompt = journey2::core::template::get_prompt_of_the_day(
+                &prompts,
+                current_date.date_naive(),
+            );
+
+            (
+                new_entry_path,
+                preamble_str
+                    + &utils::functions::generate_body_scaffold(
+                        prompt,
+                        entry_template.as_deref(),
+                        &config.habits.list,
+                    ),
+            )
+        }
+    };
 
-    // Read the modified content from the temporary file
-    let mut modified_content = String::new();
-    std::fs::File::open(&journey2::core::file::get_temp_file_path()?)
-        .expect("Failed to open temporary file")
-        .read_to_string(&mut modified_content)
-        .expect("Failed to read temporary file");
+    let modified_content =
+        match utils::functions::edit_in_editor(&config_data.editor, &initial_content)? {
+            Some(content) => content,
+            None => return Ok(()),
+        };
 
     // Check if there were any changes
-    if modified_content == preamble_str {
+    if modified_content == initial_content {
         println!("No changes found.  Will not be writing into a new entry.");
         return Ok(());
     } else {
         println!("Ok ra man");
     }
 
-    let todays_entry_path = journey2::core::file::get_path_for_todays_entry()?;
+    // Another entry may have been written in the same minute while this one was being edited
+    let mut entry_date = current_date;
+    while is_new_entry && entry_path.exists() {
+        entry_date += chrono::Duration::minutes(1);
+        entry_path = journey2::core::file::get_path_for_entry(&entry_date)?;
+    }
+    if entry_date != current_date {
+        println!(
+            "Another entry was written at {} in the meantime, so this one is filed at {}.",
+            current_date.format("%H:%M"),
+            entry_date.format("%H:%M")
+        );
+    }
 
-    let todays_entry_parent_path = std::path::Path::new(&todays_entry_path)
-        .parent()
-        .expect("Error in extracting parent of today's entry")
-        .to_str()
-        .expect("Error in converting `Path` to `&str`");
+    utils::functions::save_entry(&entry_path, &modified_content)?;
 
-    std::fs::create_dir_all(todays_entry_parent_path)?;
+    // Only remembered once it has an entry, so that abandoned entries leave no trace.  The
+    // entry is already saved, so a failure here is not worth failing `new` over.
+    if is_ad_hoc_place {
+        if let Err(e) = journey2::core::file::remember_recent_place(&place) {
+            println!(
+                "Warning: the place could not be remembered for `new --recent` ({}).",
+                e
+            );
+        }
+    }
 
-    let mut todays_entry_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open(&todays_entry_path)?;
+    utils::functions::commit_to_history(&format!(
+        "new entry {}",
+        current_date.format("%Y-%m-%d %H:%M")
+    ));
 
-    todays_entry_file.write_all(modified_content.as_bytes())?;
+    print_habits_done(&config.habits.list, &modified_content);
 
-    // Clean up the temporary file
-    std::fs::remove_file(journey2::core::file::get_temp_file_path()?)
-        .expect("Failed to remove temporary file");
     Ok(())
 }
 
+/// Prints which of the configured habits are ticked in the entry's checklist, if it has one
+fn print_habits_done(habits: &[String], contents: &str) {
+    let checklist =
+        journey2::core::habits::parse_checklist(journey2::core::entry::get_body(contents));
+    let checked: Vec<(&String, bool)> = habits
+        .iter()
+        .filter_map(|habit| Some((habit, *checklist.get(&habit.to_lowercase())?)))
+        .collect();
+
+    if checked.is_empty() {
+        return;
+    }
+
+    let done: Vec<&str> = checked
+        .iter()
+        .filter(|(_, is_done)| *is_done)
+        .map(|(habit, _)| habit.as_str())
+        .collect();
+    if done.is_empty() {
+        println!("Habits done: 0 of {}", checked.len());
+    } else {
+        println!(
+            "Habits done: {} of {} ({})",
+            done.len(),
+            checked.len(),
+            done.join(", ")
+        );
+    }
+}
+
 fn handle_open(args: OpenArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Early return if journal is not initialised
     if !utils::functions::is_journal_initialised_frontend()? {
         return Ok(()); // Early return if journal not initialised
     }
 
-    // Get all the needed data about the journal
-    let data_path = journey2::core::file::get_data_dir_path()?;
+    let config_data = journey2::core::file::get_config_from_config_file()?.defaults;
 
-    // Get the current date via time zone indicated in config file
-    let date = args.date.unwrap_or(
-        journey2::core::chrono::get_current_date_from_tz_as_str(
-            &journey2::core::file::get_config_from_config_file()?
-                .defaults
-                .timezone,
-        )?
-        .to_string()
-        .split(' ')
-        .collect::<Vec<_>>()[0]
-            .to_owned(),
-    );
+    let all_entries = journey2::core::index::load_index()?.get_entries()?;
+
+    let chosen_entry =
+        match utils::functions::choose_entry(all_entries, args.date, args.latest, args.index)? {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+
+    utils::functions::edit_entry(&config_data.editor, &chosen_entry)
+}
+
+fn handle_list(args: ListArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let from = args
+        .from
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+    let to = args
+        .to
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+
+    let index = journey2::core::index::load_index()?;
+
+    // Newest first
+    let mut entries: Vec<&journey2::core::index::IndexedEntry> = index
+        .entries
+        .values()
+        .filter(|entry| utils::functions::is_date_in_range(entry.datetime.date(), from, to))
+        .filter(|entry| {
+            args.template
+                .as_ref()
+                .is_none_or(|template| entry.templates.contains(template))
+        })
+        .filter(|entry| {
+            args.tag.as_deref().is_none_or(|tag| {
+                entry
+                    .tags
+                    .contains(&journey2::core::tags::normalise_tag(tag))
+            })
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.datetime));
+
+    if let Some(last) = args.last.or(args.count) {
+        entries.truncate(last);
+    }
+
+    if args.reverse {
+        entries.reverse();
+    }
+
+    if entries.is_empty() {
+        println!("There are no entries to list.");
+        return Ok(());
+    }
+
+    for entry in entries {
+        println!(
+            "{}  {:<30}  {}",
+            entry.datetime.format("%a %d %b %Y %H:%M"),
+            utils::functions::truncate_with_ellipsis(entry.location.as_deref().unwrap_or(""), 30),
+            utils::functions::truncate_with_ellipsis(entry.summary.as_deref().unwrap_or(""), 60)
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_show(args: ShowArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let all_entries = journey2::core::index::load_index()?.get_entries()?;
+
+    let mut matching_entries: Vec<journey2::core::file::EntryFile> = if args.latest {
+        all_entries.last().cloned().into_iter().collect()
+    } else if args.from.is_some() || args.to.is_some() {
+        let from = args
+            .from
+            .as_deref()
+            .map(utils::functions::parse_date_arg)
+            .transpose()?;
+        let to = args
+            .to
+            .as_deref()
+            .map(utils::functions::parse_date_arg)
+            .transpose()?;
+
+        all_entries
+            .into_iter()
+            .filter(|entry| utils::functions::is_date_in_range(entry.datetime.date(), from, to))
+            .collect()
+    } else {
+        let date = match args.date {
+            Some(date) => utils::functions::parse_date_arg(&date)?,
+            None => journey2::core::chrono::get_current_date_from_tz_as_str(
+                &journey2::core::file::get_config_from_config_file()?
+                    .defaults
+                    .timezone,
+            )?
+            .date_naive(),
+        };
+
+        all_entries
+            .into_iter()
+            .filter(|entry| entry.datetime.date() == date)
+            .collect()
+    };
+
+    if let Some(index) = args.index {
+        if index == 0 || index > matching_entries.len() {
+            return Err(format!(
+                "There is no entry #{} for that date.  There are only {} entries.",
+                index,
+                matching_entries.len()
+            )
+            .into());
+        }
+        matching_entries = vec![matching_entries.swap_remove(index - 1)];
+    }
+
+    if matching_entries.is_empty() {
+        println!("There are no entries matching that date.");
+        return Ok(());
+    }
+
+    let mut output = String::new();
+
+    for (i, entry) in matching_entries.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        output.push_str(&utils::functions::generate_entry_separator(&entry.datetime));
+        output.push_str(&journey2::core::file::read_entry(&entry.path)?);
+    }
+
+    if args.no_pager {
+        utils::functions::print_output(&output)?;
+    } else {
+        utils::functions::print_with_pager(&output)?;
+    }
+
+    Ok(())
+}
+
+fn handle_search(args: SearchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::IsTerminal;
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let query =
+        journey2::core::search::build_query_regex(&args.query, args.regex, args.ignore_case)?;
+
+    let from = args
+        .from
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+    let to = args
+        .to
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+
+    // Only decorate the output when a person is going to read it
+    let is_terminal = std::io::stdout().is_terminal();
 
-    let path_pattern = glob::Pattern::new(&format!(
-        "{}/{}*.txt",
-        data_path.to_string_lossy(),
-        date.split('-').collect::<Vec<_>>().join("/")
-    ))
-    .unwrap();
+    let index = journey2::core::index::load_index()?;
 
-    // Accumulate all matching files from the given glob pattern generated from
-    // the current date.
-    let mut matching_files: Vec<std::path::PathBuf> = vec![];
+    // Plain-text queries only need to look at entries that contain all of their words
+    let candidates = (!args.regex).then(|| index.get_candidates_for_text(&args.query));
 
-    for entry in walkdir::WalkDir::new(journey2::core::file::get_data_dir_path()?)
+    let mut match_count = 0;
+
+    for entry in index
+        .get_entries()?
         .into_iter()
-        .filter_map(|e| e.ok())
+        .filter(|entry| utils::functions::is_date_in_range(entry.datetime.date(), from, to))
     {
-        let path = entry.path();
-        if path_pattern.matches_path(path) {
-            matching_files.push(path.to_path_buf());
-            println!("{}", path.display());
+        let key = journey2::core::index::get_index_key(&entry.path);
+
+        if let Some(candidates) = &candidates {
+            if !candidates.contains(&key) {
+                continue;
+            }
+        }
+
+        if let Some(template) = &args.template {
+            if !index
+                .entries
+                .get(&key)
+                .is_some_and(|indexed| indexed.templates.contains(template))
+            {
+                continue;
+            }
+        }
+
+        let contents = journey2::core::file::read_entry(&entry.path)?;
+
+        let groups = journey2::core::search::find_matching_lines(&contents, &query, args.context);
+
+        if groups.is_empty() {
+            continue;
         }
+
+        let title = entry.datetime.format("%a %d %b %Y %H:%M").to_string();
+        if is_terminal {
+            println!("{}", crossterm::style::Stylize::bold(title));
+        } else {
+            println!("{}", title);
+        }
+
+        for (i, group) in groups.iter().enumerate() {
+            if i > 0 {
+                println!("  --");
+            }
+            for line in group {
+                let separator = if line.is_match { ':' } else { '-' };
+                let text = if line.is_match && is_terminal {
+                    utils::functions::highlight_matches(line.text, &query)
+                } else {
+                    line.text.to_string()
+                };
+                println!("  {:>4}{} {}", line.line_number, separator, text);
+            }
+            match_count += group.iter().filter(|line| line.is_match).count();
+        }
+        println!();
     }
 
-    println!("{:?}", matching_files);
+    if match_count == 0 {
+        println!("No entries contain \"{}\".", args.query);
+    }
 
-    // To display the dates encoded in the files' paths in a human-readable form,
-    // the absolute paths must be converted into relative paths and then parsed.
-    let matching_dates: Vec<chrono::NaiveDateTime> = matching_files
-        .into_iter()
-        .filter_map(|file| journey2::cli::utils::functions::extract_naive_datetime(
-                &(file.strip_prefix(&data_path).unwrap())
-                ).ok())
-        .collect();
+    Ok(())
+}
+
+fn handle_reindex() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let index = journey2::core::index::build_index()?;
+    journey2::core::index::save_index(&index)?;
+
+    println!(
+        "Indexed {} entries containing {} distinct words.",
+        index.entries.len(),
+        index.terms.len()
+    );
+
+    for (key, entry) in index.entries.iter() {
+        if let Some(parse_error) = &entry.parse_error {
+            println!("Warning: {} could not be read ({}).", key, parse_error);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_delete(args: DeleteArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let all_entries = journey2::core::index::load_index()?.get_entries()?;
+
+    let chosen_entry =
+        match utils::functions::choose_entry(all_entries, args.date, args.latest, args.index)? {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+
+    if !args.yes && !interaction::ask::ask_if_to_delete_entry(&chosen_entry.datetime)? {
+        return Ok(());
+    }
+
+    journey2::core::trash::move_to_trash(&chosen_entry)?;
+    journey2::core::index::forget_entry(&chosen_entry.path)?;
+    utils::functions::commit_to_history(&format!(
+        "delete entry {}",
+        chosen_entry.datetime.format("%Y-%m-%d %H:%M")
+    ));
+
+    println!(
+        "Moved the entry from {} to the trash.  Use `journey2 trash restore` to undo this.",
+        chosen_entry.datetime.format("%b %d, %Y - %H:%M")
+    );
+
+    Ok(())
+}
+
+fn handle_trash_list() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let trashed_entries = journey2::core::trash::get_trashed_entries()?;
+
+    if trashed_entries.is_empty() {
+        println!("The trash is empty.");
+        return Ok(());
+    }
+
+    for trashed in trashed_entries.iter().rev() {
+        let contents = journey2::core::file::read_entry(&trashed.entry.path)?;
+        let deleted_at = trashed
+            .deleted_at
+            .map_or("unknown".to_string(), |deleted_at| {
+                deleted_at
+                    .with_timezone(&chrono::Local)
+                    .format("%d %b %Y %H:%M")
+                    .to_string()
+            });
+
+        println!(
+            "{}  (deleted {})  {}",
+            trashed.entry.datetime.format("%a %d %b %Y %H:%M"),
+            deleted_at,
+            utils::functions::truncate_with_ellipsis(
+                journey2::core::entry::get_first_body_line(&contents).unwrap_or(""),
+                50
+            )
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_trash_restore(args: TrashRestoreArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let mut trashed_entries = journey2::core::trash::get_trashed_entries()?;
+
+    let chosen = match args.date {
+        Some(date) => {
+            let date = utils::functions::parse_date_arg(&date)?;
+            trashed_entries.retain(|trashed| trashed.entry.datetime.date() == date);
+
+            let entries: Vec<journey2::core::file::EntryFile> = trashed_entries
+                .iter()
+                .map(|trashed| trashed.entry.clone())
+                .collect();
+
+            // Pick among the trashed entries of that date the same way `open` does
+            match utils::functions::choose_entry(
+                entries,
+                Some(date.to_string()),
+                false,
+                args.index,
+            )? {
+                Some(entry) => trashed_entries
+                    .into_iter()
+                    .find(|trashed| trashed.entry.path == entry.path),
+                None => return Ok(()),
+            }
+        }
+        None => trashed_entries
+            .into_iter()
+            .max_by_key(|trashed| trashed.deleted_at),
+    };
+
+    let Some(chosen) = chosen else {
+        println!("The trash is empty.");
+        return Ok(());
+    };
+
+    journey2::core::trash::restore(&chosen)?;
+
+    if let Some(entry) = journey2::core::file::get_entry_file(&chosen.original_path)? {
+        let contents = journey2::core::file::read_entry(&entry.path)?;
+        journey2::core::index::record_entry(&entry, &contents)?;
+    }
+    utils::functions::commit_to_history(&format!(
+        "restore entry {}",
+        chosen.entry.datetime.format("%Y-%m-%d %H:%M")
+    ));
+
+    println!(
+        "Restored the entry from {}.",
+        chosen.entry.datetime.format("%b %d, %Y - %H:%M")
+    );
+
+    Ok(())
+}
+
+fn handle_trash_empty(args: TrashEmptyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let older_than = args
+        .older_than
+        .as_deref()
+        .map(utils::functions::parse_duration_arg)
+        .transpose()?;
+
+    let now = chrono::Utc::now();
+
+    let to_remove: Vec<journey2::core::trash::TrashedEntry> =
+        journey2::core::trash::get_trashed_entries()?
+            .into_iter()
+            .filter(|trashed| match older_than {
+                // Entries with no record of when they were deleted are kept, to be safe
+                Some(older_than) => trashed
+                    .deleted_at
+                    .is_some_and(|deleted_at| now - deleted_at > older_than),
+                None => true,
+            })
+            .collect();
+
+    if to_remove.is_empty() {
+        println!("There is nothing to remove from the trash.");
+        return Ok(());
+    }
+
+    if !args.yes && !interaction::ask::ask_if_to_empty_trash(to_remove.len())? {
+        return Ok(());
+    }
+
+    for trashed in to_remove.iter() {
+        journey2::core::trash::remove_permanently(trashed)?;
+    }
+    utils::functions::commit_to_history("empty trash");
 
-    let choice = journey2::cli::interaction::ask::ask_for_which_date(&matching_dates)?;
+    println!("Removed {} entries from the trash.", to_remove.len());
 
-    println!("{}", matching_dates[choice].format("%b %d, %Y - %H:%M"));
+    Ok(())
+}
+
+fn handle_places_add(alias: String) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let places = journey2::core::file::get_config_from_config_file()?.places;
+
+    if places.contains_key(&alias) && !interaction::ask::ask_if_to_overwrite_place(&alias)? {
+        return Ok(());
+    }
+
+    let (full_address, location) = interaction::ask::ask_user_for_place_location(&alias)?;
+
+    journey2::core::file::add_place_to_config_file(
+        &alias,
+        &journey2::core::Place::from_location(full_address, &location),
+    )?;
+
+    println!(
+        "Saved '{}'.  Use `journey2 new --at {}` to write from there.",
+        alias, alias
+    );
+
+    Ok(())
+}
+
+fn handle_places_remove(alias: String) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    if journey2::core::file::remove_place_from_config_file(&alias)? {
+        println!("Removed '{}'.", alias);
+    } else {
+        println!("There is no saved place called '{}'.", alias);
+    }
+
+    Ok(())
+}
+
+fn handle_places_list() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let places = journey2::core::file::get_config_from_config_file()?.places;
+
+    if places.is_empty() {
+        println!("You have no saved places yet.  Add one with `journey2 places add <alias>`.");
+        return Ok(());
+    }
+
+    for (alias, place) in places.iter() {
+        println!("{:<12}  {}", alias, place);
+    }
+
+    Ok(())
+}
+
+fn handle_config_check() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let mut problems = 0;
+
+    match journey2::core::file::get_config_from_config_file() {
+        Ok(_) => println!("Config file: OK"),
+        Err(error) => {
+            println!("Config file: {}", error);
+            problems += 1;
+        }
+    }
+
+    match utils::functions::get_preamble_template() {
+        Ok(None) => println!("Preamble template: none, the built-in preamble is used"),
+        Ok(Some(_)) => println!("Preamble template: OK"),
+        Err(error) => {
+            println!("Preamble template: {}", error);
+            problems += 1;
+        }
+    }
+
+    match journey2::core::file::get_entry_template_names() {
+        Ok(names) if names.is_empty() => println!("Entry templates: none"),
+        Ok(names) => println!("Entry templates: {}", names.join(", ")),
+        Err(error) => {
+            println!("Entry templates: {}", error);
+            problems += 1;
+        }
+    }
+
+    match journey2::core::file::get_prompts() {
+        Ok(prompts) if prompts.is_empty() => println!("Writing prompts: none"),
+        Ok(prompts) => println!("Writing prompts: {}", prompts.len()),
+        Err(error) => {
+            println!("Writing prompts: {}", error);
+            problems += 1;
+        }
+    }
+
+    if problems > 0 {
+        return Err(format!("found {} problem(s) in your configuration", problems).into());
+    }
+
+    Ok(())
+}
+
+fn handle_tags_list() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let index = journey2::core::index::load_index()?;
+    let usages = journey2::core::tags::get_tag_usages(&index);
+
+    if usages.is_empty() {
+        println!("There are no tags yet.  Add some to your entries with '#', e.g. #work");
+        return Ok(());
+    }
+
+    println!("{:<24}  ENTRIES  FIRST USED  LAST USED", "TAG");
+    for usage in usages {
+        println!(
+            "{:<24}  {:>7}  {}  {}",
+            utils::functions::truncate_with_ellipsis(&format!("#{}", usage.tag), 24),
+            usage.entry_count,
+            usage.first_used.format("%Y-%m-%d"),
+            usage.last_used.format("%Y-%m-%d")
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_tags_rename(args: TagsRenameArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let old = journey2::core::tags::normalise_tag(&args.old);
+    let new = journey2::core::tags::normalise_tag(&args.new);
+
+    if !journey2::core::tags::is_valid_tag(&new) {
+        return Err(format!("'{}' cannot be used as a tag.", args.new).into());
+    }
+    if old == new {
+        println!("The tag is already called #{}.", new);
+        return Ok(());
+    }
+
+    let index = journey2::core::index::load_index()?;
+    let is_merge = index
+        .entries
+        .values()
+        .any(|entry| entry.tags.contains(&new));
+    let entries: Vec<journey2::core::file::EntryFile> = index
+        .get_entries()?
+        .into_iter()
+        .filter(|entry| {
+            index
+                .entries
+                .get(&journey2::core::index::get_index_key(&entry.path))
+                .is_some_and(|indexed| indexed.tags.contains(&old))
+        })
+        .collect();
+
+    if entries.is_empty() {
+        println!("No entries have the tag #{}.", old);
+        return Ok(());
+    }
+
+    if !args.yes && !interaction::ask::ask_if_to_rename_tag(&old, &new, entries.len(), is_merge)? {
+        return Ok(());
+    }
+
+    let mut renamed_count = 0;
+    for entry in entries {
+        let contents = journey2::core::file::read_entry(&entry.path)?;
+
+        if let Some(renamed) = journey2::core::tags::rename_tag(&contents, &old, &new) {
+            utils::functions::save_entry(&entry.path, &renamed)?;
+            renamed_count += 1;
+        }
+    }
+    utils::functions::commit_to_history(&format!("rename tag #{} to #{}", old, new));
+
+    println!("Renamed #{} to #{} in {} entries.", old, new, renamed_count);
+
+    Ok(())
+}
+
+fn handle_people_list() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let aliases = journey2::core::file::get_config_from_config_file()?.people;
+    let index = journey2::core::index::load_index()?;
+    let people = journey2::core::people::get_people(&index, &aliases);
+
+    if people.is_empty() {
+        println!("You haven't mentioned anyone yet.  Mention people in your entries with '@', e.g. @alice");
+        return Ok(());
+    }
+
+    println!("{:<24}  MENTIONS  ENTRIES  LAST SEEN", "PERSON");
+    for person in people {
+        println!(
+            "{:<24}  {:>8}  {:>7}  {}",
+            utils::functions::truncate_with_ellipsis(&format!("@{}", person.name), 24),
+            person.mention_count,
+            person.entry_count,
+            person.last_seen.format("%Y-%m-%d")
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_people_show(args: PeopleShowArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let aliases = journey2::core::file::get_config_from_config_file()?.people;
+    let person = journey2::core::people::resolve_name(&args.name, &aliases);
+
+    let index = journey2::core::index::load_index()?;
+
+    let mut output = String::new();
+
+    // `get_entries` is oldest first
+    for entry in index.get_entries()? {
+        let mentions_person = index
+            .entries
+            .get(&journey2::core::index::get_index_key(&entry.path))
+            .is_some_and(|indexed| {
+                indexed
+                    .mentions
+                    .keys()
+                    .any(|name| journey2::core::people::resolve_name(name, &aliases) == person)
+            });
+        if !mentions_person {
+            continue;
+        }
+
+        let contents = journey2::core::file::read_entry(&entry.path)?;
+        let lines = journey2::core::people::find_mentioning_lines(
+            journey2::core::entry::get_body(&contents),
+            &person,
+            &aliases,
+        );
+        if lines.is_empty() {
+            continue;
+        }
+
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&utils::functions::generate_entry_separator(&entry.datetime));
+        for line in lines {
+            output.push_str(line.trim());
+            output.push('\n');
+        }
+    }
+
+    if output.is_empty() {
+        println!("You haven't mentioned @{} in any entry.", person);
+        return Ok(());
+    }
+
+    if args.no_pager {
+        utils::functions::print_output(&output)?;
+    } else {
+        utils::functions::print_with_pager(&output)?;
+    }
+
+    Ok(())
+}
+
+fn handle_trends(args: TrendsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use journey2::core::tracking::{Metric, Period, WeatherVariable};
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let from = args
+        .from
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+    let to = args
+        .to
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+    let period = if args.monthly {
+        Period::Month
+    } else {
+        Period::Week
+    };
+
+    let index = journey2::core::index::load_index()?;
+    let entries: Vec<&journey2::core::index::IndexedEntry> = index
+        .entries
+        .values()
+        .filter(|entry| utils::functions::is_date_in_range(entry.datetime.date(), from, to))
+        .collect();
+
+    let period_averages = journey2::core::tracking::get_period_averages(&entries, period);
+
+    if period_averages.is_empty() {
+        println!(
+            "There are no moods, energies or hours of sleep to show.  \
+            Turn them on in the [tracking] table of your config file."
+        );
+        return Ok(());
+    }
+
+    // Metrics that were never tracked are left out
+    let metrics: Vec<(usize, Metric)> = Metric::ALL
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            period_averages
+                .iter()
+                .any(|period| period.averages[*i].is_some())
+        })
+        .collect();
+
+    let format_average =
+        |average: Option<f64>| average.map_or("-".to_string(), |average| format!("{:.1}", average));
+
+    print!("{:<8}  ENTRIES", "PERIOD");
+    for (_, metric) in metrics.iter() {
+        print!("  {:>6}", metric.get_field());
+    }
+    println!();
+    for period_average in period_averages.iter() {
+        print!(
+            "{:<8}  {:>7}",
+            period_average.name, period_average.entry_count
+        );
+        for (i, _) in metrics.iter() {
+            print!("  {:>6}", format_average(period_average.averages[*i]));
+        }
+        println!();
+    }
+
+    println!();
+    for (i, metric) in metrics.iter() {
+        let values: Vec<Option<f64>> = period_averages
+            .iter()
+            .map(|period| period.averages[*i])
+            .collect();
+        println!(
+            "{:<8}  {}",
+            metric.get_field(),
+            journey2::core::tracking::get_sparkline(&values, metric.get_range())
+        );
+    }
+
+    println!("\nCorrelation with the weather, from -1 to 1 (number of entries in brackets):");
+    print!("{:<8}", "");
+    for variable in WeatherVariable::ALL {
+        print!("  {:>16}", variable.get_name());
+    }
+    println!();
+    for (_, metric) in metrics.iter() {
+        print!("{:<8}", metric.get_field());
+        for variable in WeatherVariable::ALL {
+            let (correlation, count) =
+                journey2::core::tracking::get_correlation(&entries, *metric, variable);
+            let correlation = correlation.map_or("n/a".to_string(), |r| format!("{:+.2}", r));
+            print!("  {:>16}", format!("{} ({})", correlation, count));
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+fn handle_habits(args: HabitsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use chrono::Datelike;
+    use std::io::IsTerminal;
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let config = journey2::core::file::get_config_from_config_file()?;
+    let habits = &config.habits.list;
+
+    if habits.is_empty() {
+        println!(
+            "You have no habits to track.  List them in the [habits] table of your config file."
+        );
+        return Ok(());
+    }
+
+    let today = journey2::core::chrono::get_current_date_from_tz_as_str(&config.defaults.timezone)?
+        .date_naive();
+    let month_start = match args.month {
+        Some(month) => chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+            .map_err(|_| format!("'{}' is not a valid month.  Use 'YYYY-MM'.", month))?,
+        None => today.with_day(1).unwrap_or(today),
+    };
+    let month_days: Vec<chrono::NaiveDate> = month_start
+        .iter_days()
+        .take_while(|day| day.month() == month_start.month())
+        .collect();
+
+    let index = journey2::core::index::load_index()?;
+    let habit_days: Vec<(&String, std::collections::BTreeMap<chrono::NaiveDate, bool>)> = habits
+        .iter()
+        .map(|habit| (habit, journey2::core::habits::get_habit_days(&index, habit)))
+        .collect();
+
+    let name_width = habits
+        .iter()
+        .map(|habit| habit.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("HABIT".len());
+
+    println!(
+        "{:<width$}  CURRENT STREAK  LONGEST STREAK  DONE",
+        "HABIT",
+        width = name_width
+    );
+    for (habit, days) in habit_days.iter() {
+        let summary = journey2::core::habits::get_habit_summary(days, today);
+        let completion = summary
+            .get_completion_rate()
+            .map_or("-".to_string(), |rate| {
+                format!(
+                    "{}/{} ({:.0}%)",
+                    summary.done_days,
+                    summary.tracked_days,
+                    rate * 100.0
+                )
+            });
+        println!(
+            "{:<width$}  {:>14}  {:>14}  {}",
+            habit,
+            summary.current_streak,
+            summary.longest_streak,
+            completion,
+            width = name_width
+        );
+    }
+
+    // Ticked days are filled in, and days whose checklist left the habit unticked are dotted
+    let is_terminal = std::io::stdout().is_terminal();
+    println!("\n{}", month_start.format("%B %Y"));
+    print!("{:<width$}", "", width = name_width);
+    for day in month_days.iter() {
+        print!(" {}", day.day() % 10);
+    }
+    println!();
+    for (habit, days) in habit_days.iter() {
+        print!("{:<width$}", habit, width = name_width);
+        for day in month_days.iter() {
+            match days.get(day) {
+                Some(true) if is_terminal => {
+                    print!(" {}", crossterm::style::Stylize::green("■"))
+                }
+                Some(true) => print!(" ■"),
+                Some(false) => print!(" ·"),
+                None => print!("  "),
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+fn handle_stats(args: StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    /// Number of locations listed, the rest are only counted
+    const LOCATION_COUNT: usize = 5;
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let from = args
+        .from
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+    let to = args
+        .to
+        .as_deref()
+        .map(utils::functions::parse_date_arg)
+        .transpose()?;
+
+    // A range that ends in the past has its current streak counted back from its end
+    let today = journey2::core::chrono::get_current_date_from_tz_as_str(
+        &journey2::core::file::get_config_from_config_file()?
+            .defaults
+            .timezone,
+    )?
+    .date_naive();
+    let today = to.map_or(today, |to| to.min(today));
+
+    let index = journey2::core::index::load_index()?;
+    let entries: Vec<&journey2::core::index::IndexedEntry> = index
+        .entries
+        .values()
+        .filter(|entry| utils::functions::is_date_in_range(entry.datetime.date(), from, to))
+        .collect();
+
+    let stats = journey2::core::stats::get_stats(&entries, today);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    if stats.totals.entry_count == 0 {
+        println!("There are no entries to count.");
+        return Ok(());
+    }
+
+    println!("Entries:                 {}", stats.totals.entry_count);
+    println!("Words:                   {}", stats.totals.word_count);
+    println!(
+        "Average words per entry: {:.0}",
+        stats.totals.average_word_count
+    );
+    let format_days = |count: usize| match count {
+        1 => "1 day".to_string(),
+        _ => format!("{} days", count),
+    };
+    println!(
+        "Current streak:          {}",
+        format_days(stats.current_streak)
+    );
+    println!(
+        "Longest streak:          {}",
+        format_days(stats.longest_streak)
+    );
+    if let Some(weekday) = stats.busiest_weekday {
+        println!("Busiest weekday:         {}", weekday);
+    }
+    if let Some(hour) = stats.busiest_hour {
+        println!("Busiest hour:            {:02}:00-{:02}:59", hour, hour);
+    }
+
+    if !stats.locations.is_empty() {
+        println!("\nMost-journaled locations:");
+        for location in stats.locations.iter().take(LOCATION_COUNT) {
+            println!("{:>7}  {}", location.entry_count, location.location);
+        }
+        if stats.locations.len() > LOCATION_COUNT {
+            println!(
+                "         and {} more",
+                stats.locations.len() - LOCATION_COUNT
+            );
+        }
+    }
+
+    println!("\nYEAR  ENTRIES    WORDS  AVERAGE");
+    for year in stats.years.iter() {
+        println!(
+            "{:<4}  {:>7}  {:>7}  {:>7.0}",
+            year.year,
+            year.totals.entry_count,
+            year.totals.word_count,
+            year.totals.average_word_count
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_calendar(args: CalendarArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use chrono::Datelike;
+    use journey2::core::calendar::{CalendarPeriod, Shading};
+    use std::io::IsTerminal;
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let config_data = journey2::core::file::get_config_from_config_file()?.defaults;
+
+    let mut period = match args.period {
+        Some(period) => CalendarPeriod::parse(&period).ok_or_else(|| {
+            format!(
+                "'{}' is not a valid year or month.  Use 'YYYY' or 'YYYY-MM'.",
+                period
+            )
+        })?,
+        None => CalendarPeriod::Year(
+            journey2::core::chrono::get_current_date_from_tz_as_str(&config_data.timezone)?.year(),
+        ),
+    };
+    let shading = if args.words {
+        Shading::WordCount
+    } else {
+        Shading::EntryCount
+    };
+
+    // Without a terminal to move around in, the heatmap is only printed
+    if !std::io::stdout().is_terminal() {
+        let days = journey2::core::calendar::get_day_totals(&journey2::core::index::load_index()?);
+        for line in interaction::calendar::render_calendar(period, &days, shading, false) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    let mut cursor = None;
+    loop {
+        // Reloaded every time, as the entry that was opened may have changed
+        let index = journey2::core::index::load_index()?;
+        let days = journey2::core::calendar::get_day_totals(&index);
+
+        let day = match interaction::calendar::ask_for_calendar_day(period, cursor, &days, shading)?
+        {
+            Some(day) => day,
+            None => return Ok(()),
+        };
+        cursor = Some(day);
+        period = period.get_period_of(day);
+
+        if let Some(entry) = utils::functions::choose_entry(
+            index.get_entries()?,
+            Some(day.format("%Y-%m-%d").to_string()),
+            false,
+            None,
+        )? {
+            utils::functions::edit_entry(&config_data.editor, &entry)?;
+        }
+    }
+}
+
+/// Returns the entries written on the same day of the year as `day`, in the years before `year`.
+/// The year of `day` itself is ignored.
+fn get_on_this_day_entries(
+    day: chrono::NaiveDate,
+    year: i32,
+) -> Result<Vec<journey2::core::file::EntryFile>, journey2::core::file::FileError> {
+    use chrono::Datelike;
+
+    let mut entries = journey2::core::file::get_entries_on_day_of_year(day.month(), day.day())?;
+    entries.retain(|entry| entry.datetime.year() < year);
+
+    Ok(entries)
+}
+
+/// Mentions, in a line, the entries written on this day in earlier years, if there are any
+fn print_on_this_day_teaser(date: chrono::NaiveDate) {
+    use chrono::Datelike;
+
+    // Only a teaser, so a problem reading the entries is not worth stopping `new` for
+    let Ok(entries) = get_on_this_day_entries(date, date.year()) else {
+        return;
+    };
+    let Some(oldest) = entries.first() else {
+        return;
+    };
+
+    let years = date.year() - oldest.datetime.year();
+    println!(
+        "\nOn this day: {} {} from earlier years, the oldest {} {} ago.  See `journey2 onthisday`.",
+        entries.len(),
+        if entries.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        years,
+        if years == 1 { "year" } else { "years" }
+    );
+}
+
+fn handle_on_this_day(args: OnThisDayArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use chrono::Datelike;
+
+    /// Longest excerpt of each entry's body, in characters
+    const EXCERPT_LENGTH: usize = 200;
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let today = journey2::core::chrono::get_current_date_from_tz_as_str(
+        &journey2::core::file::get_config_from_config_file()?
+            .defaults
+            .timezone,
+    )?
+    .date_naive();
+
+    // Only the month and day are used, so the year 2000 lets '02-29' be given
+    let date = match args.date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&format!("2000-{}", date), "%Y-%m-%d")
+            .map_err(|_| format!("'{}' is not a valid day.  Use 'MM-DD'.", date))?,
+        None => today,
+    };
+
+    let entries = get_on_this_day_entries(date, today.year())?;
+
+    if entries.is_empty() {
+        println!(
+            "You haven't written anything on {} in earlier years.",
+            date.format("%B %-d")
+        );
+        return Ok(());
+    }
+
+    for entry in entries.iter().rev() {
+        let contents = journey2::core::file::read_entry(&entry.path)?;
+        let (location, weather, body) = match journey2::core::entry::parse_entry(&contents) {
+            Ok(parsed) => (parsed.location, parsed.weather, parsed.body),
+            Err(_) => (
+                journey2::core::entry::get_header_field(&contents, "LOCATION"),
+                None,
+                journey2::core::entry::get_body(&contents).to_string(),
+            ),
+        };
+
+        let years = today.year() - entry.datetime.year();
+        println!(
+            "{} ({} {} ago), {}",
+            entry.datetime.year(),
+            years,
+            if years == 1 { "year" } else { "years" },
+            entry.datetime.format("%a %d %b %H:%M")
+        );
+        if let Some(location) = location {
+            println!("{}", location);
+        }
+        if let Some(weather) = weather {
+            println!("{}", utils::functions::generate_weather_line(&weather));
+        }
+        let excerpt = body.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if !excerpt.is_empty() {
+            println!(
+                "\n    {}",
+                utils::functions::truncate_with_ellipsis(&excerpt, EXCERPT_LENGTH)
+            );
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+fn handle_review(args: ReviewArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use journey2::core::tracking::Period;
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    let config_data = journey2::core::file::get_config_from_config_file()?.defaults;
+
+    let period = if args.week {
+        Period::Week
+    } else if args.year {
+        Period::Year
+    } else {
+        Period::Month
+    };
+    let date = match args.date {
+        Some(date) => utils::functions::parse_date_arg(&date)?,
+        None => journey2::core::chrono::get_current_date_from_tz_as_str(&config_data.timezone)?
+            .date_naive(),
+    };
+
+    let review_path = journey2::core::file::get_review_path(&period.get_name(date))?;
+
+    // A review that was already written is opened as it is, instead of a new draft
+    let is_new = !review_path.exists();
+    let initial_content = if is_new {
+        let (first_day, last_day) = period.get_range(date);
+        let index = journey2::core::index::load_index()?;
+        let mut entries: Vec<(&str, &journey2::core::index::IndexedEntry)> = index
+            .entries
+            .iter()
+            .filter(|(_, entry)| {
+                utils::functions::is_date_in_range(
+                    entry.datetime.date(),
+                    Some(first_day),
+                    Some(last_day),
+                )
+            })
+            .map(|(key, entry)| (key.as_str(), entry))
+            .collect();
+        entries.sort_by_key(|(_, entry)| entry.datetime);
+
+        journey2::core::review::generate_review(period, date, &entries)
+    } else {
+        println!("Opening the review of {}", period.get_name(date));
+        journey2::core::file::read_entry(&review_path)?
+    };
+
+    let modified_content =
+        match utils::functions::edit_in_editor(&config_data.editor, &initial_content)? {
+            Some(content) => content,
+            None => return Ok(()),
+        };
+
+    if modified_content == initial_content {
+        if is_new {
+            println!("No changes found.  Will not be writing the review.");
+        } else {
+            println!("No changes found.");
+        }
+        return Ok(());
+    }
+
+    journey2::core::file::write_entry(&review_path, &modified_content)?;
+    utils::functions::commit_to_history(&format!("review {}", period.get_name(date)));
+    println!("Saved the review to {}", review_path.display());
+
+    Ok(())
+}
+
+fn handle_encryption_enable() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    // Enabling it again finishes encrypting files that were left out, e.g. after an interruption
+    let mut restart_history = false;
+    if journey2::core::crypto::is_journal_encrypted()? {
+        println!("Your journal is already encrypted.  Encrypting any files that are not yet.");
+        journey2::core::crypto::get_key()?;
+    } else {
+        if journey2::core::history::is_history_enabled()? {
+            if !interaction::ask::ask_if_to_restart_history()? {
+                return Ok(());
+            }
+            restart_history = true;
+        }
+
+        let config = journey2::core::file::get_config_from_config_file()?;
+        let secret = match config.encryption.key_file {
+            Some(_) => utils::functions::get_journal_secret()?,
+            None => {
+                println!("Your journal will be encrypted with a passphrase.  If you forget it, your entries cannot be recovered.");
+                let passphrase = interaction::ask::ask_for_new_passphrase()?;
+                zeroize::Zeroizing::new(passphrase.as_bytes().to_vec())
+            }
+        };
+        journey2::core::crypto::enable_encryption(&secret)?;
+    }
+
+    let mut encrypted_count = 0;
+    for path in journey2::core::file::get_journal_files()? {
+        if journey2::core::file::set_file_encryption(&path, true)? {
+            encrypted_count += 1;
+        }
+    }
+
+    // The index holds summaries and words of the entries, so it is written again, encrypted
+    let index = journey2::core::index::build_index()?;
+    journey2::core::index::save_index(&index)?;
+    if restart_history {
+        if let Err(e) = journey2::core::history::restart_history() {
+            println!(
+                "Warning: the journal was encrypted, but its history could not be started over ({}).  Delete the .git directory in {} to remove the unencrypted versions.",
+                e,
+                journey2::core::file::get_data_dir_path()?.display()
+            );
+        }
+    } else {
+        utils::functions::commit_to_history("encrypt journal");
+    }
+
+    println!(
+        "Encrypted {} {}.  Your journal is encrypted.",
+        encrypted_count,
+        if encrypted_count == 1 {
+            "file"
+        } else {
+            "files"
+        }
+    );
+
+    Ok(())
+}
+
+fn handle_encryption_disable() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    if !journey2::core::crypto::is_journal_encrypted()? {
+        println!("Your journal is not encrypted.");
+        return Ok(());
+    }
+
+    // Unlock first, so that a wrong passphrase changes nothing
+    journey2::core::crypto::get_key()?;
+
+    let mut decrypted_count = 0;
+    for path in journey2::core::file::get_journal_files()? {
+        if journey2::core::file::set_file_encryption(&path, false)? {
+            decrypted_count += 1;
+        }
+    }
+
+    // Only turned off once every file is readable without the key
+    journey2::core::crypto::disable_encryption()?;
+    #[cfg(unix)]
+    journey2::core::agent::stop_agent()?;
+
+    let index = journey2::core::index::build_index()?;
+    journey2::core::index::save_index(&index)?;
+    utils::functions::commit_to_history("decrypt journal");
+
+    println!(
+        "Decrypted {} {}.  Your journal is no longer encrypted.",
+        decrypted_count,
+        if decrypted_count == 1 {
+            "file"
+        } else {
+            "files"
+        }
+    );
+
+    Ok(())
+}
+
+/// Why `unlock`, `lock` and the agent are not available, as the agent needs Unix sockets
+#[cfg(not(unix))]
+const UNLOCK_NOT_SUPPORTED: &str = "Unlocking the journal for a while is only supported on \
+    Unix-like systems, e.g. Linux, FreeBSD and macOS.  Each command asks for the passphrase instead.";
+
+#[cfg(unix)]
+fn handle_unlock(args: UnlockArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, Write};
+    use std::os::unix::process::CommandExt;
+
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    if !journey2::core::crypto::is_journal_encrypted()? {
+        println!("Your journal is not encrypted, so there is nothing to unlock.");
+        return Ok(());
+    }
+
+    let timeout = utils::functions::parse_duration_arg(&args.timeout)?;
+    if timeout <= chrono::Duration::zero() {
+        return Err(format!("'{}' is too short to stay unlocked for.", args.timeout).into());
+    }
+
+    // Asks for the passphrase, unless an agent is already running, which is then replaced
+    let key = journey2::core::crypto::get_key()?;
+    journey2::core::agent::stop_agent()?;
+
+    // In its own process group, so that it outlives this command and ignores Ctrl-C sent to it
+    let mut agent = std::process::Command::new(std::env::current_exe()?)
+        .arg("agent")
+        .arg("--timeout-secs")
+        .arg(timeout.num_seconds().to_string())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .process_group(0)
+        .spawn()?;
+
+    // The key is sent through a pipe, as arguments can be seen by other users
+    if let Some(mut stdin) = agent.stdin.take() {
+        stdin.write_all(key.as_bytes())?;
+    }
+
+    let mut ready = String::new();
+    if let Some(stdout) = agent.stdout.take() {
+        std::io::BufReader::new(stdout).read_line(&mut ready)?;
+    }
+    if ready.trim_end() != "ready" {
+        return Err("The agent that keeps your journal unlocked could not be started.".into());
+    }
+
+    println!(
+        "Your journal is unlocked for {}.  Run `journey2 lock` to lock it sooner.",
+        args.timeout.trim()
+    );
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn handle_unlock(_args: UnlockArgs) -> Result<(), Box<dyn std::error::Error>> {
+    Err(UNLOCK_NOT_SUPPORTED.into())
+}
+
+#[cfg(unix)]
+fn handle_lock() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    if journey2::core::agent::stop_agent()? {
+        println!("Your journal is locked.");
+    } else {
+        println!("Your journal was not unlocked.");
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn handle_lock() -> Result<(), Box<dyn std::error::Error>> {
+    Err(UNLOCK_NOT_SUPPORTED.into())
+}
+
+/** Runs the agent that `unlock` starts, which reads the key from stdin
+*/
+#[cfg(unix)]
+fn handle_agent(args: AgentArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{Read, Write};
+
+    let mut key_bytes = zeroize::Zeroizing::new(Vec::new());
+    std::io::stdin().read_to_end(&mut key_bytes)?;
+    let key = journey2::core::crypto::Key::from_bytes(&key_bytes)
+        .ok_or("The agent was not given a key.")?;
+
+    journey2::core::agent::run_agent(
+        key,
+        std::time::Duration::from_secs(args.timeout_secs),
+        || {
+            println!("ready");
+            let _ = std::io::stdout().flush();
+        },
+    )?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn handle_agent(_args: AgentArgs) -> Result<(), Box<dyn std::error::Error>> {
+    Err(UNLOCK_NOT_SUPPORTED.into())
+}
+
+/// Returns whether the journal has a history, and tells the user how to start one if not
+fn is_history_enabled_frontend() -> Result<bool, Box<dyn std::error::Error>> {
+    let is_history_enabled = journey2::core::history::is_history_enabled()?;
+
+    if !is_history_enabled {
+        println!("Your journal has no history yet.  Run `journey2 history enable` to start one.");
+    }
+
+    Ok(is_history_enabled)
+}
+
+fn handle_history_list(entry_args: HistoryEntryArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? || !is_history_enabled_frontend()? {
+        return Ok(()); // Early return if journal not initialised or has no history
+    }
+
+    // Without a date, it is the history of the whole journal
+    let entry = if entry_args.date.is_some() || entry_args.latest {
+        let all_entries = journey2::core::index::load_index()?.get_entries()?;
+        match utils::functions::choose_entry(
+            all_entries,
+            entry_args.date,
+            entry_args.latest,
+            entry_args.index,
+        )? {
+            Some(entry) => Some(entry),
+            None => return Ok(()),
+        }
+    } else {
+        None
+    };
+    let revisions =
+        journey2::core::history::get_revisions(entry.as_ref().map(|entry| entry.path.as_path()))?;
+
+    if revisions.is_empty() {
+        println!("There are no revisions yet.");
+        return Ok(());
+    }
+
+    for revision in revisions.iter() {
+        println!(
+            "{}  {}  {}",
+            revision.hash,
+            revision.datetime.format("%a, %Y %b %d %H:%M"),
+            revision.message
+        );
+    }
+
+    Ok(())
+}
+
+fn handle_history_diff(args: HistoryDiffArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? || !is_history_enabled_frontend()? {
+        return Ok(()); // Early return if journal not initialised or has no history
+    }
+
+    // Without a date, it is the history of the whole journal
+    let entry_args = args.entry;
+    let entry = if entry_args.date.is_some() || entry_args.latest {
+        let all_entries = journey2::core::index::load_index()?.get_entries()?;
+        match utils::functions::choose_entry(
+            all_entries,
+            entry_args.date,
+            entry_args.latest,
+            entry_args.index,
+        )? {
+            Some(entry) => Some(entry),
+            None => return Ok(()),
+        }
+    } else {
+        None
+    };
+    let diff = journey2::core::history::get_diff(
+        entry.as_ref().map(|entry| entry.path.as_path()),
+        args.revision.as_deref(),
+    )?;
+
+    if diff.is_empty() {
+        println!("There are no revisions yet.");
+        return Ok(());
+    }
+
+    utils::functions::print_with_pager(&diff)?;
+
+    Ok(())
+}
+
+fn handle_history_enable() -> Result<(), Box<dyn std::error::Error>> {
+    if !utils::functions::is_journal_initialised_frontend()? {
+        return Ok(()); // Early return if journal not initialised
+    }
+
+    if journey2::core::history::enable_history()? {
+        println!(
+            "Your journal's history is now kept with git, in {}.",
+            journey2::core::file::get_data_dir_path()?.display()
+        );
+    } else {
+        println!("Your journal's history is already kept.");
+    }
 
     Ok(())
 }
@@ -5,7 +5,6 @@
 
 
 use crate as journey2;
-use crate::cli::utils;
 
 pub(crate) fn is_journal_initialised_frontend() -> Result<bool, journey2::core::file::FileError> {
     let is_journal_initialised = journey2::core::file::is_journal_initialised()?;
@@ -19,59 +18,484 @@ pub(crate) fn is_journal_initialised_frontend() -> Result<bool, journey2::core::
     Ok(is_journal_initialised)
 }
 
+/// Commits the journal's changes to its history, if it has one.
+///
+/// A failure is reported but does not fail the command, as the changes themselves are already saved.
+pub(crate) fn commit_to_history(message: &str) {
+    if let Err(e) = journey2::core::history::commit_changes(message) {
+        println!(
+            "Warning: the changes were saved, but could not be recorded in the journal's history ({}).",
+            e
+        );
+    }
+}
+
+/// Returns what the key of an encrypted journal is derived from: the contents of the
+/// key file set in the config file, or else the passphrase, which the user is asked for.
+pub(crate) fn get_journal_secret() -> Result<zeroize::Zeroizing<Vec<u8>>, Box<dyn std::error::Error>>
+{
+    let config = journey2::core::file::get_config_from_config_file()?;
+
+    if let Some(key_file) = config.encryption.key_file {
+        let contents = std::fs::read(&key_file)
+            .map_err(|e| format!("Failed to read the key file {}: {}", key_file.display(), e))?;
+        return Ok(zeroize::Zeroizing::new(contents));
+    }
+
+    let passphrase = journey2::cli::interaction::ask::ask_for_passphrase()?;
+
+    Ok(zeroize::Zeroizing::new(passphrase.as_bytes().to_vec()))
+}
+
+/// Writes an entry to disk and records it in the journal's index.
+///
+/// A failure to update the index is reported but does not fail the write,
+/// as the entry itself is already safe.
+pub(crate) fn save_entry(
+    path: &std::path::Path,
+    contents: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    journey2::core::file::write_entry(path, contents)?;
+
+    // Edits to the preamble may have left it unreadable, which the user should fix
+    if let Err(e) = journey2::core::entry::parse_entry(contents) {
+        println!(
+            "Warning: the entry was saved, but its preamble could not be read ({}).",
+            e
+        );
+    }
+
+    if let Some(entry) = journey2::core::file::get_entry_file(path)? {
+        if let Err(e) = journey2::core::index::record_entry(&entry, contents) {
+            println!(
+                "Could not update the journal's index: {}.  Try running `journey2 reindex`.",
+                e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Picks a single entry from `entries`, asking the user if it is ambiguous.
+///
+/// # Arguments
+///
+/// * `entries` - The entries to choose from, sorted from oldest to newest.
+/// * `date` - Only consider entries of this date ('YYYY-MM-DD').  Defaults to today.
+/// * `latest` - Pick the newest of all `entries`, regardless of `date`.
+/// * `index` - Pick the N-th entry (counting from 1) of that date instead of asking.
+///
+/// # Returns
+///
+/// `None` if there are no entries matching that date.
+pub(crate) fn choose_entry(
+    entries: Vec<journey2::core::file::EntryFile>,
+    date: Option<String>,
+    latest: bool,
+    index: Option<usize>,
+) -> Result<Option<journey2::core::file::EntryFile>, Box<dyn std::error::Error>> {
+    let mut matching_entries: Vec<journey2::core::file::EntryFile> = if latest {
+        entries.last().cloned().into_iter().collect()
+    } else {
+        // Get the current date via time zone indicated in config file
+        let date = match date {
+            Some(date) => parse_date_arg(&date)?,
+            None => journey2::core::chrono::get_current_date_from_tz_as_str(
+                &journey2::core::file::get_config_from_config_file()?
+                    .defaults
+                    .timezone,
+            )?
+            .date_naive(),
+        };
+
+        entries
+            .into_iter()
+            .filter(|entry| entry.datetime.date() == date)
+            .collect()
+    };
+
+    if matching_entries.is_empty() {
+        println!("There are no entries matching that date.");
+        return Ok(None);
+    }
+
+    let choice = match index {
+        Some(index) if index >= 1 && index <= matching_entries.len() => index - 1,
+        Some(index) => {
+            return Err(format!(
+                "There is no entry #{} for that date.  There are only {} entries.",
+                index,
+                matching_entries.len()
+            )
+            .into())
+        }
+        None => {
+            let matching_dates: Vec<chrono::NaiveDateTime> = matching_entries
+                .iter()
+                .map(|entry| entry.datetime)
+                .collect();
+            journey2::cli::interaction::ask::ask_for_which_date(&matching_dates)?
+        }
+    };
+
+    Ok(Some(matching_entries.swap_remove(choice)))
+}
+
+/// Opens an entry in the user's editor, saving it if it was changed
+pub(crate) fn edit_entry(
+    editor: &str,
+    entry: &journey2::core::file::EntryFile,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Opening {}", entry.datetime.format("%b %d, %Y - %H:%M"));
+
+    let original_content = journey2::core::file::read_entry(&entry.path)?;
+
+    let modified_content = match edit_in_editor(editor, &original_content)? {
+        Some(content) => content,
+        None => return Ok(()),
+    };
+
+    if modified_content == original_content {
+        println!("No changes found.");
+        return Ok(());
+    }
+
+    save_entry(&entry.path, &modified_content)?;
+    commit_to_history(&format!(
+        "edit entry {}",
+        entry.datetime.format("%Y-%m-%d %H:%M")
+    ));
+
+    Ok(())
+}
+
+/// Parses a date given on the command line in 'YYYY-MM-DD' format
+pub(crate) fn parse_date_arg(date: &str) -> chrono::ParseResult<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+}
+
+/// Parses a duration given on the command line, e.g. '30d', '2w', '12h' or '15m'
+pub(crate) fn parse_duration_arg(duration: &str) -> Result<chrono::Duration, String> {
+    let invalid = || {
+        format!(
+            "'{}' is not a valid duration.  Try something like '30d', '2w', '12h' or '15m'.",
+            duration
+        )
+    };
+
+    let duration = duration.trim();
+    let unit_start = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let amount: i64 = duration[..unit_start].parse().map_err(|_| invalid())?;
+
+    match &duration[unit_start..] {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a time given on the command line in 'HH:MM' format
+pub(crate) fn parse_time_arg(time: &str) -> chrono::ParseResult<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(time, "%H:%M")
+}
+
+/// Checks if `date` falls within the (inclusive) bounds, where a missing bound is unbounded
+pub(crate) fn is_date_in_range(
+    date: chrono::NaiveDate,
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+) -> bool {
+    from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+}
+
+/// Shortens `text` to at most `max_chars` characters, marking the cut with an ellipsis
+pub(crate) fn truncate_with_ellipsis(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Prints `text` through the user's `$PAGER` (or `less`) if stdout is a terminal.
+///
+/// When stdout is piped, or when the pager cannot be started, `text` is
+/// printed as-is so that it can be consumed by other tools.
+pub(crate) fn print_with_pager(text: &str) -> std::io::Result<()> {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdout().is_terminal() {
+        return print_output(text);
+    }
+
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    let mut pager_parts = pager.split_whitespace();
+
+    let pager_proc = pager_parts.next().and_then(|program| {
+        std::process::Command::new(program)
+            .args(pager_parts)
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .ok()
+    });
+
+    match pager_proc {
+        Some(mut pager_proc) => {
+            if let Some(mut stdin) = pager_proc.stdin.take() {
+                // The user may quit the pager before reading everything
+                match stdin.write_all(text.as_bytes()) {
+                    Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e),
+                    _ => (),
+                }
+            }
+            pager_proc.wait()?;
+            Ok(())
+        }
+        None => print_output(text),
+    }
+}
+
+/// Prints `text` as it is.  Output piped into e.g. `head` may be closed before all of it is
+/// written, which is not an error.
+pub(crate) fn print_output(text: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut stdout = std::io::stdout().lock();
+    match write!(stdout, "{}", text).and_then(|_| stdout.flush()) {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// Styles every part of `line` that matches `query` so it stands out in the terminal
+pub(crate) fn highlight_matches(line: &str, query: &regex::Regex) -> String {
+    use crossterm::style::Stylize;
+
+    let mut highlighted = String::new();
+    let mut last_end = 0;
+
+    for found in query.find_iter(line) {
+        highlighted.push_str(&line[last_end..found.start()]);
+        highlighted.push_str(&found.as_str().red().bold().to_string());
+        last_end = found.end();
+    }
+    highlighted.push_str(&line[last_end..]);
+
+    highlighted
+}
+
+/// Generates the line printed above each entry when several entries are shown together
+pub(crate) fn generate_entry_separator(datetime: &chrono::NaiveDateTime) -> String {
+    let title = format!("=== {} ", datetime.format("%a, %d %B %Y, %H:%M"));
+    format!("{:=<80}\n", title)
+}
+
+/// Reads and checks the user's preamble template, if they have written one.
+///
+/// Errors in the template are reported along with the template's path.
+pub(crate) fn get_preamble_template(
+) -> Result<Option<journey2::core::template::PreambleTemplate>, Box<dyn std::error::Error>> {
+    let template = match journey2::core::file::get_preamble_template()? {
+        Some(template) => template,
+        None => return Ok(None),
+    };
+
+    match journey2::core::template::PreambleTemplate::parse(&template) {
+        Ok(template) => Ok(Some(template)),
+        Err(error) => Err(format!(
+            "{}: {}",
+            journey2::core::file::get_preamble_template_path()?.display(),
+            error
+        )
+        .into()),
+    }
+}
+
+/// Reads the entry template `name` for `new --template`.
+///
+/// Unknown names are reported along with the templates there are.
+pub(crate) fn get_entry_template(name: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if let Some(template) = journey2::core::file::get_entry_template(name)? {
+        return Ok(template);
+    }
+
+    let names = journey2::core::file::get_entry_template_names()?;
+    let message = if names.is_empty() {
+        format!(
+            "There is no entry template called '{}'.  Templates go in {}",
+            name,
+            journey2::core::file::get_entry_templates_dir_path()?.display()
+        )
+    } else {
+        format!(
+            "There is no entry template called '{}'.  The templates are: {}",
+            name,
+            names.join(", ")
+        )
+    };
+
+    Err(message.into())
+}
+
+/// Generates the preamble of a new entry, using the user's preamble template if given.
+///
+/// `fields` are added to the header, e.g. the entry template's name or the mood.
+/// Without `current_weather`, the preamble has no weather lines.
 pub(crate) fn generate_preamble(
     location_full_name: &str,
     current_date: &chrono::DateTime<chrono_tz::Tz>,
-    current_weather: journey2::core::weather::Weather,
+    current_weather: Option<journey2::core::weather::Weather>,
+    entry_format: journey2::core::entry::EntryFormat,
+    template: Option<&journey2::core::template::PreambleTemplate>,
+    fields: Vec<(String, String)>,
 ) -> String {
-    format!(
-        "DATE: {}\n\
-        LOCATION: {}\n\
-        \n\
-        Temperature: {} C, feels like {} C, {}.\n\
-        UV Index: {}  Sunrise: {}   Sunset: {}\n\
-        Rain: {} mm\n\
-        Winds: {} km/h {}\n\
-        Pressure: {} hPa\n\
-        Humidity: {}%\n\
-        Visibility: {} km\n\
-        ",
-        current_date.format("%a, %Y %b %d %H:%M:%S %Z (%:z)"),
+    let mut entry = journey2::core::entry::Entry::new(
+        current_date,
         location_full_name,
-        current_weather.temperature,
-        current_weather.apparent_temperature,
-        utils::enums::get_weather_map()
-            .get(&current_weather.weather_code)
-            .unwrap_or(&"Unknown conditions"),
-        current_weather.uv_index,
-        current_weather.sunrise,
-        current_weather.sunset,
-        current_weather.rain,
-        current_weather.windspeed,
-        journey2::core::helper::get_direction(current_weather.winddirection),
-        current_weather.pressure,
-        current_weather.humidity,
-        current_weather.visibility / 1000.0
+        current_weather,
+        entry_format,
+    );
+    entry.fields = fields;
+
+    match template {
+        Some(template) => entry.to_string_with_preamble(&template.render(&entry)),
+        None => entry.to_string(),
+    }
+}
+
+/// Generates the short preamble of a new section in a day's entry.
+///
+/// Used in `per-day` mode, where the day's entry already has the full preamble.
+/// `location_full_name` is only given when it differs from the one in the day's preamble.
+pub(crate) fn generate_sub_preamble(
+    current_date: &chrono::DateTime<chrono_tz::Tz>,
+    location_full_name: Option<&str>,
+    current_weather: Option<&journey2::core::weather::Weather>,
+    entry_template_name: Option<&str>,
+) -> String {
+    format!(
+        "\n\
+        TIME: {}\n\
+        {}\
+        {}\
+        {}\
+        \n",
+        current_date.format("%H:%M:%S %Z (%:z)"),
+        location_full_name.map_or(String::new(), |location| format!(
+            "Location: {}\n",
+            location
+        )),
+        entry_template_name.map_or(String::new(), |name| format!("Template: {}\n", name)),
+        current_weather.map_or(String::new(), |weather| format!(
+            "Weather: {}\n",
+            generate_weather_line(weather)
+        )),
+    )
+}
+
+/// Summarises the weather in a line, e.g. for the sub-preamble of a section
+pub(crate) fn generate_weather_line(weather: &journey2::core::weather::Weather) -> String {
+    format!(
+        "{} C, feels like {} C, {}.  Rain: {} mm  Winds: {} km/h {}",
+        weather.temperature,
+        weather.apparent_temperature,
+        journey2::core::weather::get_weather_description(weather.weather_code),
+        weather.rain,
+        weather.windspeed,
+        journey2::core::helper::get_direction(weather.winddirection),
     )
 }
 
-pub(crate) fn extract_naive_datetime(
-    filename: &std::path::Path,
-) -> chrono::ParseResult<chrono::NaiveDateTime> {
-    // Ths file name we receive would probably have the format:
-    // `YYYY//MM//DD.HH-mm.txt`.
-    //
-    // Of course, as we allow other options for `open`, we MAY
-    // have to accept other file names that have different
-    // starting relative paths.
-    // Create a PathBuf (for example)
-
-    // Convert PathBuf to string with forward slashes
-    let path_string = filename
-        .with_extension("")
-        .to_str()
-        .unwrap()
-        .replace("\\", "/");
-
-    chrono::NaiveDateTime::parse_from_str(&path_string, "%Y/%m/%d.%H-%M")
+/// Generates what goes in the body of a new entry before the user writes anything:
+/// the prompt of the day, the entry template, then the habit checklist.  Empty if there is none.
+pub(crate) fn generate_body_scaffold(
+    prompt: Option<&str>,
+    entry_template: Option<&str>,
+    habits: &[String],
+) -> String {
+    let mut scaffold = String::new();
+
+    if let Some(prompt) = prompt {
+        scaffold.push_str(&format!("\n> {}\n", prompt));
+    }
+
+    if let Some(entry_template) = entry_template {
+        scaffold.push('\n');
+        scaffold.push_str(entry_template);
+        if !scaffold.ends_with('\n') {
+            scaffold.push('\n');
+        }
+    }
+
+    if !habits.is_empty() {
+        scaffold.push('\n');
+        scaffold.push_str(&journey2::core::habits::generate_checklist(habits));
+    }
+
+    scaffold
+}
+
+/// Lets the user edit `initial_contents` in their editor via a temporary file.
+///
+/// The file is only readable by the user, and is overwritten before it is removed,
+/// so that no plaintext of an encrypted journal is left behind.
+///
+/// # Returns
+///
+/// `Some` with the file's contents after the editor exits, or `None` if the
+/// editor did not exit successfully.  The temporary file is removed either way.
+pub(crate) fn edit_in_editor(
+    editor: &str,
+    initial_contents: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let mut temp_file = tempfile::Builder::new()
+        .prefix("journey2-")
+        .suffix(".txt")
+        .tempfile()?;
+
+    std::io::Write::write_all(&mut temp_file, initial_contents.as_bytes())?;
+    temp_file.as_file().sync_all()?;
+
+    // Invoke the editor as a subprocess
+    let editor_proc = std::process::Command::new(editor)
+        .arg(temp_file.path())
+        .stdin(std::process::Stdio::inherit())
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit())
+        .status();
+
+    // Read the modified content from the temporary file, then shred it
+    let modified_content = std::fs::read_to_string(temp_file.path());
+    shred_file(temp_file.path())?;
+    temp_file.close()?;
+
+    let editor_proc = editor_proc.map_err(|e| format!("Failed to start {}: {}", editor, e))?;
+    let modified_content = modified_content?;
+
+    if !editor_proc.success() {
+        println!("{} was not successful", editor);
+        return Ok(None);
+    }
+
+    Ok(Some(modified_content))
+}
+
+/// Overwrites a file's contents with zeros.
+///
+/// Editors that save by replacing the file leave the old one to the file system,
+/// so this is only as thorough as the editor and file system allow.
+fn shred_file(path: &std::path::Path) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let length = file.metadata()?.len() as usize;
+
+    std::io::Write::write_all(&mut file, &vec![0u8; length])?;
+    file.sync_all()
 }
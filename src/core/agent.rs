@@ -0,0 +1,256 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the agent that keeps an encrypted journal unlocked between commands
+//!
+//! `unlock` starts the agent as a background process that holds the journal's
+//! key and hands it out over a Unix socket, `journey2/agent.sock` in
+//! `$XDG_RUNTIME_DIR`, or in a directory of the user's own in the temporary
+//! directory.  Only the user can enter that directory, and the agent only
+//! answers processes of the same user.  The agent exits, forgetting the key,
+//! when it times out or is asked to by `lock`.
+//!
+//! Unix sockets are only used on Unix-like systems, so this module is only
+//! compiled there.
+
+use std::io::{BufRead, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::core;
+
+/// Asks the agent for the key
+const REQUEST_KEY: &str = "key";
+
+/// Asks the agent to exit
+const REQUEST_LOCK: &str = "lock";
+
+/// How often the agent checks whether it has timed out while no one is asking for the key
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How long the agent waits for a request before giving up on the connection
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+fn get_user_id() -> libc::uid_t {
+    // SAFETY: getuid always succeeds and has no preconditions
+    unsafe { libc::getuid() }
+}
+
+/// Returns the user id of the process at the other end of `stream`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_peer_user_id(stream: &UnixStream) -> std::io::Result<libc::uid_t> {
+    let mut credentials = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut length = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+    // SAFETY: `credentials` and `length` point to writable values of the sizes given
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut libc::ucred as *mut libc::c_void,
+            &mut length,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(credentials.uid)
+}
+
+/// Returns the user id of the process at the other end of `stream`
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn get_peer_user_id(stream: &UnixStream) -> std::io::Result<libc::uid_t> {
+    let (mut uid, mut gid) = (0, 0);
+
+    // SAFETY: `uid` and `gid` point to writable values
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(uid)
+}
+
+/// Returns the directory of the agent's socket.  Sockets do not belong in the data
+/// directory, which may be synced to other computers or kept on a network drive.
+fn get_agent_dir_path() -> std::path::PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(runtime_dir) => std::path::PathBuf::from(runtime_dir).join(env!("CARGO_PKG_NAME")),
+        // Named after the user, as the temporary directory is shared by all users
+        None => std::env::temp_dir().join(format!("{}-{}", env!("CARGO_PKG_NAME"), get_user_id())),
+    }
+}
+
+fn get_agent_socket_path() -> std::path::PathBuf {
+    get_agent_dir_path().join("agent.sock")
+}
+
+/// Checks that only the user can enter the agent's directory.  In the temporary
+/// directory, another user could have made it first to listen in on the key.
+fn check_agent_dir(agent_dir: &std::path::Path) -> Result<(), core::file::FileError> {
+    let metadata = std::fs::symlink_metadata(agent_dir)?;
+
+    if !metadata.is_dir() || metadata.uid() != get_user_id() || metadata.mode() & 0o077 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!(
+                "{} must be a directory that only you can use.  Remove it, and try again.",
+                agent_dir.display()
+            ),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Sends a request to the agent and returns its response
+fn send_request(request: &str) -> Result<zeroize::Zeroizing<Vec<u8>>, core::file::FileError> {
+    check_agent_dir(&get_agent_dir_path())?;
+
+    let mut stream = UnixStream::connect(get_agent_socket_path())?;
+    if get_peer_user_id(&stream)? != get_user_id() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "the agent belongs to another user",
+        )
+        .into());
+    }
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    stream.write_all(format!("{}\n", request).as_bytes())?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut response = zeroize::Zeroizing::new(Vec::new());
+    stream.read_to_end(&mut response)?;
+
+    Ok(response)
+}
+
+/// Returns the key held by the agent, or `None` if no agent is running
+pub(crate) fn request_key() -> Option<core::crypto::Key> {
+    if !get_agent_socket_path().exists() {
+        return None;
+    }
+
+    core::crypto::Key::from_bytes(&send_request(REQUEST_KEY).ok()?)
+}
+
+/// Stops the agent, if it is running.
+///
+/// # Returns
+///
+/// Whether an agent was running
+pub(crate) fn stop_agent() -> Result<bool, core::file::FileError> {
+    let socket_path = get_agent_socket_path();
+    if !socket_path.exists() {
+        return Ok(false);
+    }
+
+    match send_request(REQUEST_LOCK) {
+        Ok(_) => Ok(true),
+        Err(_) => {
+            // Left behind by an agent that did not exit cleanly
+            std::fs::remove_file(&socket_path)?;
+            Ok(false)
+        }
+    }
+}
+
+/// Hands out `key` to whoever asks through the socket, until `timeout` has passed or
+/// the agent is asked to stop.
+///
+/// # Arguments
+///
+/// * `on_ready` - Called once the socket is listening, e.g. to tell `unlock` that it can exit.
+pub(crate) fn run_agent(
+    key: core::crypto::Key,
+    timeout: std::time::Duration,
+    on_ready: impl FnOnce(),
+) -> Result<(), core::file::FileError> {
+    let agent_dir = get_agent_dir_path();
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&agent_dir)?;
+    check_agent_dir(&agent_dir)?;
+
+    let socket_path = get_agent_socket_path();
+    if socket_path.exists() {
+        std::fs::remove_file(&socket_path)?;
+    }
+    let listener = UnixListener::bind(&socket_path)?;
+    std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(0o600))?;
+    listener.set_nonblocking(true)?;
+
+    on_ready();
+
+    // Wall-clock time, as `Instant` stops while the computer sleeps, which would leave the
+    // journal unlocked for however long it slept on top of `timeout`
+    let deadline = std::time::SystemTime::now() + timeout;
+    while std::time::SystemTime::now() < deadline {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                // Only the user's own processes get the key, whatever the directory's permissions
+                if !get_peer_user_id(&stream).is_ok_and(|uid| uid == get_user_id()) {
+                    continue;
+                }
+
+                // A client that sends nothing, or goes away, is not worth exiting over
+                if let Ok(false) = answer_request(stream, &key, deadline) {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(POLL_INTERVAL)
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(&socket_path);
+                return Err(e.into());
+            }
+        }
+    }
+
+    std::fs::remove_file(&socket_path)?;
+
+    Ok(())
+}
+
+/// Answers a single request.  The key is not handed out once `deadline` has passed, e.g.
+/// while the request was being read.
+///
+/// # Returns
+///
+/// Whether the agent should keep running
+fn answer_request(
+    mut stream: UnixStream,
+    key: &core::crypto::Key,
+    deadline: std::time::SystemTime,
+) -> std::io::Result<bool> {
+    // Accepted sockets inherit the listener's non-blocking mode on some platforms
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request = String::new();
+    std::io::BufReader::new(&stream)
+        .take(64)
+        .read_line(&mut request)?;
+
+    match request.trim_end() {
+        REQUEST_KEY if std::time::SystemTime::now() >= deadline => Ok(false),
+        REQUEST_KEY => {
+            stream.write_all(key.as_bytes())?;
+            Ok(true)
+        }
+        REQUEST_LOCK => Ok(false),
+        _ => Ok(true),
+    }
+}
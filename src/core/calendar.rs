@@ -0,0 +1,142 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the days and shades of the heatmap that `calendar` draws
+
+use std::collections::BTreeMap;
+
+use chrono::Datelike;
+
+use crate::core;
+
+/// Number of shades, including the one for days without entries
+pub(crate) const LEVEL_COUNT: usize = 5;
+
+/// enum CalendarPeriod
+/// What `calendar` shows: a whole year, or a single month
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CalendarPeriod {
+    Year(i32),
+    /// Holds the month's first day
+    Month(chrono::NaiveDate),
+}
+
+impl CalendarPeriod {
+    /// Parses a period given as `YYYY` or `YYYY-MM`
+    pub(crate) fn parse(period: &str) -> Option<Self> {
+        let period = period.trim();
+
+        match period.len() {
+            4 => period
+                .parse()
+                .ok()
+                .filter(|year| chrono::NaiveDate::from_ymd_opt(*year, 1, 1).is_some())
+                .map(CalendarPeriod::Year),
+            7 => chrono::NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d")
+                .ok()
+                .map(CalendarPeriod::Month),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_first_day(&self) -> chrono::NaiveDate {
+        match self {
+            CalendarPeriod::Year(year) => {
+                chrono::NaiveDate::from_ymd_opt(*year, 1, 1).unwrap_or_default()
+            }
+            CalendarPeriod::Month(first_day) => *first_day,
+        }
+    }
+
+    pub(crate) fn get_last_day(&self) -> chrono::NaiveDate {
+        let next_first_day = match self {
+            CalendarPeriod::Year(year) => chrono::NaiveDate::from_ymd_opt(year + 1, 1, 1),
+            CalendarPeriod::Month(first_day) => {
+                first_day.checked_add_months(chrono::Months::new(1))
+            }
+        };
+
+        next_first_day
+            .and_then(|day| day.pred_opt())
+            .unwrap_or(chrono::NaiveDate::MAX)
+    }
+
+    /// Returns every day of the period, in order
+    pub(crate) fn get_days(&self) -> Vec<chrono::NaiveDate> {
+        let last_day = self.get_last_day();
+
+        self.get_first_day()
+            .iter_days()
+            .take_while(|day| *day <= last_day)
+            .collect()
+    }
+
+    /// Returns the day closest to `day` within the period
+    pub(crate) fn clamp(&self, day: chrono::NaiveDate) -> chrono::NaiveDate {
+        day.clamp(self.get_first_day(), self.get_last_day())
+    }
+
+    /// Returns the period that holds `day`, of the same kind as this one
+    pub(crate) fn get_period_of(&self, day: chrono::NaiveDate) -> Self {
+        match self {
+            CalendarPeriod::Year(_) => CalendarPeriod::Year(day.year()),
+            CalendarPeriod::Month(_) => CalendarPeriod::Month(day.with_day(1).unwrap_or(day)),
+        }
+    }
+}
+
+/// enum Shading
+/// What the heatmap's shades stand for
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Shading {
+    EntryCount,
+    WordCount,
+}
+
+/// struct DayTotals
+/// What was written on a single day
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct DayTotals {
+    pub(crate) entry_count: usize,
+    pub(crate) word_count: usize,
+}
+
+impl DayTotals {
+    pub(crate) fn get(&self, shading: Shading) -> usize {
+        match shading {
+            Shading::EntryCount => self.entry_count,
+            Shading::WordCount => self.word_count,
+        }
+    }
+}
+
+/// Returns the totals of every day with an entry
+pub(crate) fn get_day_totals(
+    index: &core::index::JournalIndex,
+) -> BTreeMap<chrono::NaiveDate, DayTotals> {
+    let mut days: BTreeMap<chrono::NaiveDate, DayTotals> = BTreeMap::new();
+
+    for entry in index.entries.values() {
+        let day = days.entry(entry.datetime.date()).or_default();
+        day.entry_count += 1;
+        day.word_count += entry.word_count;
+    }
+
+    days
+}
+
+/// Returns the shade of `value`, from 0 for nothing to `LEVEL_COUNT - 1` for `max`
+pub(crate) fn get_level(value: usize, max: usize) -> usize {
+    if value == 0 || max == 0 {
+        return 0;
+    }
+
+    (value * (LEVEL_COUNT - 1))
+        .div_ceil(max)
+        .clamp(1, LEVEL_COUNT - 1)
+}
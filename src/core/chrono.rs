@@ -27,6 +27,65 @@ pub(crate) fn get_current_date_from_tz_as_str(
     Ok(chrono::Utc::now().with_timezone(&timezone))
 }
 
+/// Returns the given local date and time in the specified timezone.
+///
+/// # Arguments
+///
+/// * `tz_as_str` - The IANA name of the timezone, as in `get_current_date_from_tz_as_str`.
+/// * `date` - The date in that timezone.
+/// * `time` - The time of day in that timezone.
+///
+/// # Note
+///
+/// When `time` happens twice that day (e.g. when clocks are turned back), the
+/// earlier one is used.  When `time` is skipped (e.g. when clocks are turned
+/// forward), an error is returned.
+pub(crate) fn get_date_from_tz_as_str(
+    tz_as_str: &str,
+    date: chrono::NaiveDate,
+    time: chrono::NaiveTime,
+) -> Result<chrono::DateTime<chrono_tz::Tz>, Box<dyn std::error::Error>> {
+    let timezone: chrono_tz::Tz = tz_as_str.parse()?;
+
+    chrono::TimeZone::from_local_datetime(&timezone, &date.and_time(time))
+        .earliest()
+        .ok_or_else(|| format!("{} {} does not exist in {}.", date, time, tz_as_str).into())
+}
+
+/// Returns the current and the longest run of consecutive days in `days`.
+///
+/// The current run ends `today`, or yesterday if `today` is not in `days` yet,
+/// so that a run is not broken before the day is over.
+pub(crate) fn get_streaks(
+    days: &std::collections::BTreeSet<chrono::NaiveDate>,
+    today: chrono::NaiveDate,
+) -> (usize, usize) {
+    let mut longest_streak = 0;
+    let mut streak = 0;
+    let mut previous_day: Option<chrono::NaiveDate> = None;
+    for day in days.iter() {
+        streak = match previous_day {
+            Some(previous_day) if previous_day.succ_opt() == Some(*day) => streak + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(streak);
+        previous_day = Some(*day);
+    }
+
+    let mut day = if days.contains(&today) {
+        Some(today)
+    } else {
+        today.pred_opt()
+    };
+    let mut current_streak = 0;
+    while let Some(current_day) = day.filter(|day| days.contains(day)) {
+        current_streak += 1;
+        day = current_day.pred_opt();
+    }
+
+    (current_streak, longest_streak)
+}
+
 pub fn preprocess_datetime_for_url(date: &str) -> (String, usize) {
     // Split the combined date and time string
     let mut date_iter = date.split_whitespace();
@@ -0,0 +1,360 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the encryption of entries at rest
+//!
+//! A journal is encrypted when its data directory has a `.encryption.json`,
+//! which holds the salt and parameters that the key is derived from, with
+//! Argon2id, out of a passphrase or the contents of a key file.  Each
+//! encrypted file starts with `MAGIC`, followed by a random nonce and the
+//! contents, encrypted with XChaCha20-Poly1305.  Files without `MAGIC` are
+//! read as they are, so a journal can be part encrypted while it is being
+//! converted.
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+
+use crate::core;
+
+/// The start of every encrypted file.  It has NUL bytes, so it is never the start of an entry.
+const MAGIC: &[u8] = b"JOURNEY2\0ENCRYPTED\0";
+
+const NONCE_LENGTH: usize = 24;
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+
+/// Encrypted into `.encryption.json`, to tell a wrong passphrase apart from a damaged file
+const KEY_CHECK: &[u8] = b"journey2";
+
+/// Version of the encryption scheme, written to `.encryption.json`
+const ENCRYPTION_VERSION: u32 = 1;
+
+/// Returns what the key is derived from, e.g. by asking the user for the passphrase
+pub(crate) type SecretProvider =
+    fn() -> Result<zeroize::Zeroizing<Vec<u8>>, Box<dyn std::error::Error>>;
+
+/// Set by the CLI, as only it can ask the user for anything
+static SECRET_PROVIDER: std::sync::OnceLock<SecretProvider> = std::sync::OnceLock::new();
+
+/// The key, once the journal has been unlocked.  It is only derived once per run.
+static KEY: std::sync::OnceLock<Key> = std::sync::OnceLock::new();
+
+/// enum CryptoError
+/// Represents errors that can occur while encrypting or decrypting the journal
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug)]
+pub(crate) enum CryptoError {
+    /// The passphrase or key file could not be had
+    NoSecret(String),
+    WrongSecret,
+    Damaged,
+    KeyDerivation(String),
+    InvalidInfo(String),
+}
+
+impl std::error::Error for CryptoError {}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoError::NoSecret(ref reason) => {
+                write!(
+                    f,
+                    "The journal is encrypted and could not be unlocked: {}",
+                    reason
+                )
+            }
+            CryptoError::WrongSecret => write!(f, "The passphrase or key file is wrong."),
+            CryptoError::Damaged => write!(
+                f,
+                "A file could not be decrypted.  It is damaged, or was encrypted with another key."
+            ),
+            CryptoError::KeyDerivation(ref reason) => {
+                write!(f, "The key could not be derived: {}", reason)
+            }
+            CryptoError::InvalidInfo(ref reason) => {
+                write!(
+                    f,
+                    "The journal's .encryption.json cannot be read: {}",
+                    reason
+                )
+            }
+        }
+    }
+}
+
+/// struct Key
+/// The key that files are encrypted with.  It is wiped from memory when dropped.
+////////////////////////////////////////////////////////////////////////////////////////////////////
+pub(crate) struct Key(zeroize::Zeroizing<[u8; KEY_LENGTH]>);
+
+impl Key {
+    /// Returns the key held in `bytes`, e.g. as sent by the agent, or `None` if they are not a key
+    #[cfg(unix)]
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != KEY_LENGTH {
+            return None;
+        }
+
+        let mut key = zeroize::Zeroizing::new([0u8; KEY_LENGTH]);
+        key.copy_from_slice(bytes);
+
+        Some(Key(key))
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+/// struct EncryptionInfo
+/// What is needed to derive and check the key, as kept in `.encryption.json`
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct EncryptionInfo {
+    version: u32,
+    salt: Vec<u8>,
+    /// Argon2id's memory cost, in KiB
+    memory_cost: u32,
+    time_cost: u32,
+    parallelism: u32,
+    /// `KEY_CHECK`, encrypted with the key
+    key_check: Vec<u8>,
+}
+
+fn get_encryption_info_path() -> Result<std::path::PathBuf, core::file::FileError> {
+    Ok(core::file::get_data_dir_path()?.join(".encryption.json"))
+}
+
+/// Returns whether the journal's files are, or are to be, encrypted
+pub(crate) fn is_journal_encrypted() -> Result<bool, core::file::FileError> {
+    Ok(get_encryption_info_path()?.exists())
+}
+
+fn read_encryption_info() -> Result<EncryptionInfo, core::file::FileError> {
+    let bytes = std::fs::read(get_encryption_info_path()?)?;
+
+    serde_json::from_slice(&bytes).map_err(|e| CryptoError::InvalidInfo(e.to_string()).into())
+}
+
+/// Sets where the key is derived from when the journal first needs unlocking
+pub(crate) fn set_secret_provider(provider: SecretProvider) {
+    let _ = SECRET_PROVIDER.set(provider);
+}
+
+fn derive_key(secret: &[u8], info: &EncryptionInfo) -> Result<Key, CryptoError> {
+    let params = argon2::Params::new(
+        info.memory_cost,
+        info.time_cost,
+        info.parallelism,
+        Some(KEY_LENGTH),
+    )
+    .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+
+    let mut key = zeroize::Zeroizing::new([0u8; KEY_LENGTH]);
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(secret, &info.salt, key.as_mut())
+        .map_err(|e| CryptoError::KeyDerivation(e.to_string()))?;
+
+    Ok(Key(key))
+}
+
+/// Returns the journal's key, unlocking the journal first if this is the first time it is needed
+pub(crate) fn get_key() -> Result<&'static Key, core::file::FileError> {
+    if let Some(key) = KEY.get() {
+        return Ok(key);
+    }
+
+    let info = read_encryption_info()?;
+    if info.version != ENCRYPTION_VERSION {
+        return Err(
+            CryptoError::InvalidInfo(format!("version {} is not supported", info.version)).into(),
+        );
+    }
+
+    // An agent started by `unlock` saves asking for the passphrase again
+    #[cfg(unix)]
+    if let Some(key) = core::agent::request_key().filter(|key| is_key_of(key, &info)) {
+        return Ok(KEY.get_or_init(|| key));
+    }
+
+    let provider = SECRET_PROVIDER
+        .get()
+        .ok_or_else(|| CryptoError::NoSecret("there is no way to ask for the passphrase".into()))?;
+    let secret = provider().map_err(|e| CryptoError::NoSecret(e.to_string()))?;
+
+    let key = derive_key(&secret, &info)?;
+    if !is_key_of(&key, &info) {
+        return Err(CryptoError::WrongSecret.into());
+    }
+
+    Ok(KEY.get_or_init(|| key))
+}
+
+/// Returns whether `key` is the one that `info` was written with
+fn is_key_of(key: &Key, info: &EncryptionInfo) -> bool {
+    decrypt(key, &info.key_check).is_ok_and(|key_check| key_check[..] == *KEY_CHECK)
+}
+
+/// Returns whether `bytes` are the contents of an encrypted file
+pub(crate) fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub(crate) fn encrypt(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = chacha20poly1305::XChaCha20Poly1305::new(key.0.as_ref().into());
+    let nonce = chacha20poly1305::XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| CryptoError::Damaged)?;
+
+    let mut encrypted = Vec::with_capacity(MAGIC.len() + NONCE_LENGTH + ciphertext.len());
+    encrypted.extend_from_slice(MAGIC);
+    encrypted.extend_from_slice(&nonce);
+    encrypted.extend_from_slice(&ciphertext);
+
+    Ok(encrypted)
+}
+
+pub(crate) fn decrypt(
+    key: &Key,
+    encrypted: &[u8],
+) -> Result<zeroize::Zeroizing<Vec<u8>>, CryptoError> {
+    let rest = encrypted
+        .strip_prefix(MAGIC)
+        .filter(|rest| rest.len() >= NONCE_LENGTH)
+        .ok_or(CryptoError::Damaged)?;
+    let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+
+    let cipher = chacha20poly1305::XChaCha20Poly1305::new(key.0.as_ref().into());
+    let plaintext = cipher
+        .decrypt(chacha20poly1305::XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| CryptoError::Damaged)?;
+
+    Ok(zeroize::Zeroizing::new(plaintext))
+}
+
+/// Turns encryption on with a key derived from `secret`, and unlocks the journal with it.
+///
+/// Only `.encryption.json` is written: the files themselves are encrypted as they are
+/// next written, see `core::file::set_file_encryption`.
+pub(crate) fn enable_encryption(secret: &[u8]) -> Result<(), core::file::FileError> {
+    let mut salt = vec![0u8; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+
+    let mut info = EncryptionInfo {
+        version: ENCRYPTION_VERSION,
+        salt,
+        memory_cost: argon2::Params::DEFAULT_M_COST,
+        time_cost: argon2::Params::DEFAULT_T_COST,
+        parallelism: argon2::Params::DEFAULT_P_COST,
+        key_check: vec![],
+    };
+    let key = derive_key(secret, &info)?;
+    info.key_check = encrypt(&key, KEY_CHECK)?;
+
+    let contents =
+        serde_json::to_vec_pretty(&info).map_err(|e| CryptoError::InvalidInfo(e.to_string()))?;
+    std::fs::write(get_encryption_info_path()?, contents)?;
+
+    let _ = KEY.set(key);
+
+    Ok(())
+}
+
+/// Turns encryption off.  The files must have been decrypted first.
+pub(crate) fn disable_encryption() -> Result<(), core::file::FileError> {
+    std::fs::remove_file(get_encryption_info_path()?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Derives a key from `secret` with the cheapest parameters Argon2 takes, to keep tests fast
+    fn derive_test_key(secret: &[u8]) -> (Key, EncryptionInfo) {
+        let mut info = EncryptionInfo {
+            version: ENCRYPTION_VERSION,
+            salt: vec![7u8; SALT_LENGTH],
+            memory_cost: argon2::Params::MIN_M_COST,
+            time_cost: argon2::Params::MIN_T_COST,
+            parallelism: argon2::Params::MIN_P_COST,
+            key_check: vec![],
+        };
+        let key = derive_key(secret, &info).unwrap();
+        info.key_check = encrypt(&key, KEY_CHECK).unwrap();
+
+        (key, info)
+    }
+
+    #[test]
+    fn encrypted_contents_round_trip() {
+        let (key, _) = derive_test_key(b"pw");
+        let plaintext =
+            b"DATE: Tue, 2023 Aug 22 23:26:41 PST (+08:00)\n\nWent to the market today.\n";
+
+        let encrypted = encrypt(&key, plaintext).unwrap();
+
+        assert!(is_encrypted(&encrypted));
+        assert!(!is_encrypted(plaintext));
+        assert_eq!(decrypt(&key, &encrypted).unwrap()[..], plaintext[..]);
+    }
+
+    #[test]
+    fn wrong_key_is_not_the_journals() {
+        let (key, info) = derive_test_key(b"pw");
+        let (wrong_key, _) = derive_test_key(b"wrong");
+
+        assert!(is_key_of(&key, &info));
+        assert!(!is_key_of(&wrong_key, &info));
+    }
+
+    #[test]
+    fn damaged_contents_are_not_decrypted() {
+        let (key, _) = derive_test_key(b"pw");
+        let encrypted = encrypt(&key, b"Went to the market today.").unwrap();
+
+        let mut flipped = encrypted.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(matches!(decrypt(&key, &flipped), Err(CryptoError::Damaged)));
+
+        let truncated = &encrypted[..MAGIC.len() + NONCE_LENGTH - 1];
+        assert!(matches!(
+            decrypt(&key, truncated),
+            Err(CryptoError::Damaged)
+        ));
+    }
+
+    #[test]
+    fn plaintext_files_are_read_as_they_are() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("01.08-00.txt");
+        std::fs::write(&path, "Went to the market today.\n").unwrap();
+
+        assert_eq!(
+            core::file::read_journal_file(&path).unwrap()[..],
+            b"Went to the market today.\n"[..]
+        );
+    }
+
+    #[test]
+    fn files_already_as_asked_are_left_alone() {
+        let (key, _) = derive_test_key(b"pw");
+        let dir = tempfile::tempdir().unwrap();
+
+        let encrypted_path = dir.path().join("encrypted.txt");
+        let encrypted = encrypt(&key, b"Went to the market today.").unwrap();
+        std::fs::write(&encrypted_path, &encrypted).unwrap();
+        assert!(!core::file::set_file_encryption(&encrypted_path, true).unwrap());
+        assert_eq!(std::fs::read(&encrypted_path).unwrap(), encrypted);
+
+        let plaintext_path = dir.path().join("plaintext.txt");
+        std::fs::write(&plaintext_path, "Went to the market today.").unwrap();
+        assert!(!core::file::set_file_encryption(&plaintext_path, false).unwrap());
+    }
+}
@@ -0,0 +1,980 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the structure of journal entries and functions for reading them
+//!
+//! An entry starts with a preamble: a header of `KEY: value` lines (`DATE:`,
+//! `LOCATION:`, ...), a blank line, and the weather lines.  Everything after
+//! the preamble is the body, which is left exactly as the user wrote it.
+//!
+//! With `entry_format="front-matter"`, the preamble is preceded by a block of
+//! TOML between `+++` lines that holds the same data as plain numbers, along
+//! with the version of its schema.  When reading such an entry, the front
+//! matter takes precedence over the preamble below it.
+
+use std::sync::OnceLock;
+
+use crate::core;
+
+/// Labels of the weather lines that `generate_preamble` writes
+const WEATHER_LABELS: [&str; 7] = [
+    "Temperature:",
+    "UV Index:",
+    "Rain:",
+    "Winds:",
+    "Pressure:",
+    "Humidity:",
+    "Visibility:",
+];
+
+/// Format of the date in the `DATE:` line, without the time zone's abbreviation and offset
+pub(crate) const DATE_FORMAT: &str = "%a, %Y %b %d %H:%M:%S";
+
+/// Header field naming the entry template that `new --template` started the entry with
+pub(crate) const TEMPLATE_FIELD: &str = "TEMPLATE";
+
+/// The line before and after the front matter
+const FRONT_MATTER_DELIMITER: &str = "+++";
+
+/// Version of the front matter's schema.  Entries with a newer schema are not read.
+pub(crate) const FRONT_MATTER_SCHEMA: u32 = 1;
+
+/// enum EntryFormat
+/// How the preamble of new entries is written
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum EntryFormat {
+    /// Only the human-readable preamble (`"plain"`)
+    #[default]
+    Plain,
+    /// TOML front matter, followed by the human-readable preamble (`"front-matter"`)
+    FrontMatter,
+}
+
+/// struct FrontMatter
+/// The TOML block at the top of entries written in the `front-matter` format
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct FrontMatter {
+    schema: u32,
+    date: chrono::DateTime<chrono::FixedOffset>,
+    timezone_abbreviation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    /// Tables have to come after plain values in TOML, hence the order of these last two
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    fields: std::collections::BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weather: Option<core::weather::Weather>,
+}
+
+/// struct Entry
+/// A journal entry, read from or to be written as text
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Entry {
+    pub(crate) datetime: chrono::DateTime<chrono::FixedOffset>,
+    /// e.g. `PST`.  Kept so that the `DATE:` line can be written back as it was
+    pub(crate) timezone_abbreviation: String,
+    pub(crate) location: Option<String>,
+    /// Any other `KEY: value` lines of the header, in the order they appear
+    pub(crate) fields: Vec<(String, String)>,
+    pub(crate) weather: Option<core::weather::Weather>,
+    pub(crate) body: String,
+    pub(crate) format: EntryFormat,
+}
+
+impl Entry {
+    /// Creates an entry with an empty body
+    pub(crate) fn new(
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+        location: &str,
+        weather: Option<core::weather::Weather>,
+        format: EntryFormat,
+    ) -> Self {
+        Entry {
+            datetime: datetime.fixed_offset(),
+            timezone_abbreviation: datetime.format("%Z").to_string(),
+            location: Some(location.to_string()),
+            fields: vec![],
+            weather,
+            body: String::new(),
+            format,
+        }
+    }
+
+    /// Writes the entry with `preamble` in place of the built-in one, e.g. a preamble from a template.
+    ///
+    /// Fields that the preamble leaves out, e.g. the mood, are added to the end of its
+    /// header, so that they can still be read back.
+    pub(crate) fn to_string_with_preamble(&self, preamble: &str) -> String {
+        let mut output = String::new();
+        self.write_front_matter(&mut output)
+            .expect("the front matter is always valid TOML");
+
+        // The header is up to the first blank line, as in `parse_entry`
+        let header_length = preamble
+            .split_inclusive('\n')
+            .take_while(|line| !line.trim().is_empty())
+            .map(str::len)
+            .sum();
+        let (header, rest) = preamble.split_at(header_length);
+
+        output.push_str(header);
+        if !header.is_empty() && !header.ends_with('\n') {
+            output.push('\n');
+        }
+        for (key, value) in self.fields.iter() {
+            if get_header_field(header, key).is_none() {
+                output.push_str(&format!("{}: {}\n", key, value));
+            }
+        }
+        output.push_str(rest);
+        output.push_str(&self.body);
+
+        output
+    }
+
+    /// Writes the front matter with its `+++` lines, or nothing for entries in the `plain` format
+    fn write_front_matter(&self, f: &mut impl std::fmt::Write) -> std::fmt::Result {
+        if self.format == EntryFormat::FrontMatter {
+            let front_matter =
+                toml::to_string(&self.to_front_matter()).map_err(|_| std::fmt::Error)?;
+            writeln!(f, "{}", FRONT_MATTER_DELIMITER)?;
+            write!(f, "{}", front_matter)?;
+            writeln!(f, "{}", FRONT_MATTER_DELIMITER)?;
+        }
+
+        Ok(())
+    }
+
+    fn to_front_matter(&self) -> FrontMatter {
+        FrontMatter {
+            schema: FRONT_MATTER_SCHEMA,
+            date: self.datetime,
+            timezone_abbreviation: self.timezone_abbreviation.clone(),
+            location: self.location.clone(),
+            fields: self.fields.iter().cloned().collect(),
+            weather: self.weather.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_front_matter(f)?;
+
+        writeln!(
+            f,
+            "DATE: {} {} ({})",
+            self.datetime.format(DATE_FORMAT),
+            self.timezone_abbreviation,
+            self.datetime.format("%:z")
+        )?;
+        if let Some(location) = &self.location {
+            writeln!(f, "LOCATION: {}", location)?;
+        }
+        for (key, value) in self.fields.iter() {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        writeln!(f)?;
+
+        if let Some(weather) = &self.weather {
+            writeln!(
+                f,
+                "Temperature: {} C, feels like {} C, {}.",
+                weather.temperature,
+                weather.apparent_temperature,
+                core::weather::get_weather_description(weather.weather_code)
+            )?;
+            writeln!(
+                f,
+                "UV Index: {}  Sunrise: {}   Sunset: {}",
+                weather
+                    .uv_index
+                    .map_or("n/a".to_string(), |uv_index| uv_index.to_string()),
+                weather.sunrise,
+                weather.sunset
+            )?;
+            writeln!(f, "Rain: {} mm", weather.rain)?;
+            writeln!(
+                f,
+                "Winds: {} km/h {}",
+                weather.windspeed,
+                core::helper::get_direction(weather.winddirection)
+            )?;
+            writeln!(f, "Pressure: {} hPa", weather.pressure)?;
+            writeln!(f, "Humidity: {}%", weather.humidity)?;
+            writeln!(
+                f,
+                "Visibility: {}",
+                weather.visibility.map_or("n/a".to_string(), |visibility| {
+                    format!("{} km", visibility / 1000.0)
+                })
+            )?;
+        }
+
+        write!(f, "{}", self.body)
+    }
+}
+
+/// enum EntryParseErrorKind
+/// What went wrong while reading an entry
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum EntryParseErrorKind {
+    MissingDate,
+    ExpectedHeaderField,
+    InvalidDate(String),
+    InvalidWeatherLine(&'static str),
+    UnknownWeatherDescription(String),
+    MissingWeatherLine(&'static str),
+    UnterminatedFrontMatter,
+    InvalidFrontMatter(String),
+    UnsupportedSchema(i64),
+}
+
+/// struct EntryParseError
+/// An error while reading an entry, with the line (counting from 1) where it happened
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EntryParseError {
+    pub(crate) line: usize,
+    pub(crate) kind: EntryParseErrorKind,
+}
+
+impl std::fmt::Display for EntryParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            EntryParseErrorKind::MissingDate => write!(f, "the entry has no `DATE:` line"),
+            EntryParseErrorKind::ExpectedHeaderField => {
+                write!(f, "expected a `KEY: value` line or a blank line")
+            }
+            EntryParseErrorKind::InvalidDate(ref date) => write!(
+                f,
+                "'{}' is not a date like 'Tue, 2023 Aug 22 23:26:41 PST (+08:00)'",
+                date
+            ),
+            EntryParseErrorKind::InvalidWeatherLine(label) => {
+                write!(f, "the `{}` line is not in the expected format", label)
+            }
+            EntryParseErrorKind::UnknownWeatherDescription(ref description) => {
+                write!(f, "'{}' is not a known weather description", description)
+            }
+            EntryParseErrorKind::MissingWeatherLine(label) => {
+                write!(f, "the weather starting here has no `{}` line", label)
+            }
+            EntryParseErrorKind::UnterminatedFrontMatter => write!(
+                f,
+                "the front matter has no closing `{}` line",
+                FRONT_MATTER_DELIMITER
+            ),
+            EntryParseErrorKind::InvalidFrontMatter(ref message) => {
+                write!(f, "invalid front matter: {}", message)
+            }
+            EntryParseErrorKind::UnsupportedSchema(schema) => write!(
+                f,
+                "front matter schema {} is not supported (expected at most {})",
+                schema, FRONT_MATTER_SCHEMA
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EntryParseError {}
+
+/// Returns the regex matching the value of a weather line, given its label
+fn get_weather_line_regex(label: &str) -> &'static regex::Regex {
+    static REGEXES: OnceLock<Vec<(&'static str, regex::Regex)>> = OnceLock::new();
+
+    let regexes = REGEXES.get_or_init(|| {
+        let num = r"(-?\d+(?:\.\d+)?)";
+        [
+            (
+                "Temperature:",
+                format!(r"^{num}\s*C,\s*feels like\s*{num}\s*C,\s*(.+?)\.?$"),
+            ),
+            (
+                "UV Index:",
+                format!(r"^(n/a|{num})\s+Sunrise:\s*(\S*)\s+Sunset:\s*(\S*)$"),
+            ),
+            ("Rain:", format!(r"^{num}\s*mm$")),
+            ("Winds:", format!(r"^{num}\s*km/h\s*([NSEW]{{1,3}})$")),
+            ("Pressure:", format!(r"^{num}\s*hPa$")),
+            ("Humidity:", format!(r"^{num}\s*%$")),
+            ("Visibility:", format!(r"^(?:{num}\s*km|n/a)$")),
+        ]
+        .into_iter()
+        .map(|(label, pattern)| {
+            (
+                label,
+                regex::Regex::new(&format!("(?i){}", pattern)).unwrap(),
+            )
+        })
+        .collect()
+    });
+
+    regexes
+        .iter()
+        .find(|(known_label, _)| *known_label == label)
+        .map(|(_, regex)| regex)
+        .unwrap()
+}
+
+/// Parses the value of a `DATE:` line, e.g. `Tue, 2023 Aug 22 23:26:41 PST (+08:00)`.
+///
+/// The weekday is ignored, as it follows from the date anyway.
+fn parse_date(value: &str) -> Option<(chrono::DateTime<chrono::FixedOffset>, String)> {
+    static REGEX: OnceLock<regex::Regex> = OnceLock::new();
+
+    let regex = REGEX.get_or_init(|| {
+        regex::Regex::new(
+            r"^(?:\w+,\s*)?(\d{4}\s+\w{3}\s+\d{1,2}\s+\d{1,2}:\d{2}(?::\d{2})?)\s+(\S*?)\s*\(([+-]\d{2}:?\d{2})\)$",
+        )
+        .unwrap()
+    });
+
+    let captures = regex.captures(value.trim())?;
+
+    let naive_datetime = chrono::NaiveDateTime::parse_from_str(&captures[1], "%Y %b %d %H:%M:%S")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(&captures[1], "%Y %b %d %H:%M"))
+        .ok()?;
+    let offset: chrono::FixedOffset = captures[3].parse().ok()?;
+    let datetime = chrono::TimeZone::from_local_datetime(&offset, &naive_datetime).single()?;
+
+    Some((datetime, captures[2].to_string()))
+}
+
+/// Weather values read so far, as the weather lines can come in any order
+#[derive(Default)]
+struct PartialWeather {
+    temperature: Option<(f64, f64, usize)>,
+    uv_index: Option<(Option<f64>, String, String)>,
+    rain: Option<f64>,
+    winds: Option<(f64, f64)>,
+    pressure: Option<f64>,
+    humidity: Option<f64>,
+    visibility: Option<Option<f64>>,
+}
+
+impl PartialWeather {
+    /// Reads a weather line into the weather read so far
+    fn read_line(&mut self, label: &'static str, value: &str) -> Result<(), EntryParseErrorKind> {
+        let captures = get_weather_line_regex(label)
+            .captures(value.trim())
+            .ok_or(EntryParseErrorKind::InvalidWeatherLine(label))?;
+
+        // The regexes only match valid numbers
+        let number = |i: usize| captures.get(i).and_then(|m| m.as_str().parse::<f64>().ok());
+
+        match label {
+            "Temperature:" => {
+                let description = captures[3].trim();
+                let weather_code = core::weather::get_weather_code(description).ok_or(
+                    EntryParseErrorKind::UnknownWeatherDescription(description.to_string()),
+                )?;
+                self.temperature = Some((number(1).unwrap(), number(2).unwrap(), weather_code));
+            }
+            "UV Index:" => {
+                self.uv_index = Some((number(2), captures[3].to_string(), captures[4].to_string()))
+            }
+            "Rain:" => self.rain = number(1),
+            "Winds:" => {
+                let direction = core::helper::get_degrees(&captures[2])
+                    .ok_or(EntryParseErrorKind::InvalidWeatherLine(label))?;
+                self.winds = Some((number(1).unwrap(), direction));
+            }
+            "Pressure:" => self.pressure = number(1),
+            "Humidity:" => self.humidity = number(1),
+            "Visibility:" => {
+                // Rounded to the millimetre so that kilometres convert back exactly
+                self.visibility =
+                    Some(number(1).map(|visibility| (visibility * 1_000_000.0).round() / 1000.0))
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn into_weather(self) -> Result<core::weather::Weather, &'static str> {
+        let (temperature, apparent_temperature, weather_code) =
+            self.temperature.ok_or("Temperature:")?;
+        let (uv_index, sunrise, sunset) = self.uv_index.ok_or("UV Index:")?;
+        let (windspeed, winddirection) = self.winds.ok_or("Winds:")?;
+
+        Ok(core::weather::Weather {
+            temperature,
+            apparent_temperature,
+            weather_code,
+            rain: self.rain.ok_or("Rain:")?,
+            windspeed,
+            winddirection,
+            pressure: self.pressure.ok_or("Pressure:")?,
+            humidity: self.humidity.ok_or("Humidity:")?,
+            visibility: self.visibility.ok_or("Visibility:")?,
+            uv_index,
+            sunrise,
+            sunset,
+        })
+    }
+}
+
+/// Finds the front matter at the top of an entry, if it has any.
+///
+/// Returns the TOML between the `+++` lines and the offset of the rest of the entry.
+fn find_front_matter(contents: &str) -> Result<Option<(&str, usize)>, EntryParseError> {
+    let mut lines = contents.split_inclusive('\n');
+
+    let start = match lines.next() {
+        Some(line) if line.trim_end() == FRONT_MATTER_DELIMITER => line.len(),
+        _ => return Ok(None),
+    };
+
+    let mut offset = start;
+    for line in lines {
+        if line.trim_end() == FRONT_MATTER_DELIMITER {
+            return Ok(Some((&contents[start..offset], offset + line.len())));
+        }
+        offset += line.len();
+    }
+
+    Err(EntryParseError {
+        line: 1,
+        kind: EntryParseErrorKind::UnterminatedFrontMatter,
+    })
+}
+
+/// Reads the TOML of an entry's front matter, i.e. the lines after the opening `+++`
+fn parse_front_matter(front_matter: &str) -> Result<FrontMatter, EntryParseError> {
+    // Turns a position in the front matter into a line of the entry, after the opening `+++`
+    let to_error = |error: toml::de::Error| EntryParseError {
+        line: error.span().map_or(1, |span| {
+            front_matter[..span.start].matches('\n').count() + 2
+        }),
+        kind: EntryParseErrorKind::InvalidFrontMatter(error.message().trim().replace('\n', "; ")),
+    };
+
+    // Checked before the rest, as newer schemas may not look like this one at all
+    let table: toml::Table = toml::from_str(front_matter).map_err(to_error)?;
+    if let Some(schema) = table.get("schema").and_then(|schema| schema.as_integer()) {
+        if schema > FRONT_MATTER_SCHEMA as i64 {
+            return Err(EntryParseError {
+                line: 1,
+                kind: EntryParseErrorKind::UnsupportedSchema(schema),
+            });
+        }
+    }
+
+    toml::from_str(front_matter).map_err(to_error)
+}
+
+/// Reads an entry written by `new`, including entries whose header the user has edited.
+///
+/// Entries with front matter are read from the front matter alone; the
+/// preamble below it is only skipped to find the body.
+///
+/// Header keys and weather labels are matched case-insensitively, the header
+/// may have extra `KEY: value` lines, and the weather lines may come in any
+/// order.  An entry without weather lines has no weather.
+///
+/// # Note
+///
+/// The preamble only has the wind's compass direction, so the wind direction
+/// read back is the middle of that direction, e.g. 202.5° for `SSW`.
+pub(crate) fn parse_entry(contents: &str) -> Result<Entry, EntryParseError> {
+    if let Some((front_matter, offset)) = find_front_matter(contents)? {
+        let front_matter = parse_front_matter(front_matter)?;
+
+        return Ok(Entry {
+            datetime: front_matter.date,
+            timezone_abbreviation: front_matter.timezone_abbreviation,
+            location: front_matter.location,
+            fields: front_matter.fields.into_iter().collect(),
+            weather: front_matter.weather,
+            body: get_body(&contents[offset..]).to_string(),
+            format: EntryFormat::FrontMatter,
+        });
+    }
+
+    let error = |line: usize, kind: EntryParseErrorKind| EntryParseError { line, kind };
+
+    let mut date: Option<(chrono::DateTime<chrono::FixedOffset>, String)> = None;
+    let mut location: Option<String> = None;
+    let mut fields: Vec<(String, String)> = vec![];
+
+    let mut weather = PartialWeather::default();
+    let mut first_weather_line: Option<usize> = None;
+
+    let mut lines = contents.split_inclusive('\n').enumerate().peekable();
+    let mut offset = 0;
+
+    // The header, up to the first blank line
+    for (i, line) in lines.by_ref() {
+        offset += line.len();
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            break;
+        }
+
+        let (key, value) = trimmed
+            .split_once(':')
+            .ok_or(error(i + 1, EntryParseErrorKind::ExpectedHeaderField))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        if key.eq_ignore_ascii_case("DATE") {
+            date = Some(parse_date(value).ok_or(error(
+                i + 1,
+                EntryParseErrorKind::InvalidDate(value.to_string()),
+            ))?);
+        } else if key.eq_ignore_ascii_case("LOCATION") {
+            location = Some(value.to_string());
+        } else {
+            fields.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    let (datetime, timezone_abbreviation) =
+        date.ok_or(error(1, EntryParseErrorKind::MissingDate))?;
+
+    // The weather lines, up to the first line that is not one
+    while let Some((i, line)) = lines.peek() {
+        let trimmed = line.trim();
+
+        let Some(label) = get_weather_label(trimmed) else {
+            break;
+        };
+
+        weather
+            .read_line(label, &trimmed[label.len()..])
+            .map_err(|kind| error(i + 1, kind))?;
+        first_weather_line.get_or_insert(i + 1);
+
+        offset += line.len();
+        lines.next();
+    }
+
+    let weather = match first_weather_line {
+        Some(first_weather_line) => Some(weather.into_weather().map_err(|label| {
+            error(
+                first_weather_line,
+                EntryParseErrorKind::MissingWeatherLine(label),
+            )
+        })?),
+        None => None,
+    };
+
+    Ok(Entry {
+        datetime,
+        timezone_abbreviation,
+        location,
+        fields,
+        weather,
+        body: contents[offset..].to_string(),
+        format: EntryFormat::Plain,
+    })
+}
+
+/// Returns the entry without its front matter, or the whole entry if it has none
+fn skip_front_matter(contents: &str) -> &str {
+    match find_front_matter(contents) {
+        Ok(Some((_, offset))) => &contents[offset..],
+        _ => contents,
+    }
+}
+
+/// Returns the value of `LOCATION` or of a key of `[fields]` in the entry's front matter,
+/// if it has any
+fn get_front_matter_field(contents: &str, key: &str) -> Option<String> {
+    let (front_matter, _) = find_front_matter(contents).ok()??;
+    let front_matter: FrontMatter = toml::from_str(front_matter).ok()?;
+
+    if key.eq_ignore_ascii_case("LOCATION") {
+        return front_matter.location;
+    }
+
+    front_matter
+        .fields
+        .into_iter()
+        .find(|(field_key, _)| field_key.eq_ignore_ascii_case(key))
+        .map(|(_, value)| value)
+}
+
+/// Returns the value of a `KEY: value` line in the entry's header, if present.
+///
+/// As in `parse_entry`, a value in the front matter takes precedence over the header.
+/// Like `get_body`, this never fails, even for entries that cannot be parsed.
+///
+/// # Arguments
+///
+/// * `contents` - The full contents of the entry.
+/// * `key` - The key to look for, e.g. `LOCATION`.  Matching is case-insensitive.
+pub(crate) fn get_header_field(contents: &str, key: &str) -> Option<String> {
+    if let Some(value) = get_front_matter_field(contents, key) {
+        return Some(value);
+    }
+
+    skip_front_matter(contents)
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(line_key, _)| line_key.trim().eq_ignore_ascii_case(key))
+        .map(|(_, value)| value.trim().to_string())
+}
+
+/// Returns the weather label that `line` starts with.  Any case will do, as entries are
+/// edited by hand.
+fn get_weather_label(line: &str) -> Option<&'static str> {
+    WEATHER_LABELS.into_iter().find(|label| {
+        line.get(..label.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(label))
+    })
+}
+
+/// Returns the part of the entry that the user wrote, i.e. everything after the preamble.
+///
+/// Unlike `parse_entry`, this never fails, so it is used for entries that cannot
+/// be parsed.  Entries without a preamble are returned whole.
+pub(crate) fn get_body(contents: &str) -> &str {
+    let contents = skip_front_matter(contents);
+
+    if get_header_field(contents, "DATE").is_none() {
+        return contents;
+    }
+
+    let mut offset = 0;
+    let mut in_header = true;
+
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if in_header {
+            if trimmed.is_empty() {
+                in_header = false;
+            }
+        } else if get_weather_label(trimmed).is_none() {
+            break;
+        }
+        offset += line.len();
+    }
+
+    &contents[offset..]
+}
+
+/// Returns the first non-blank line of the entry's body, if any.
+pub(crate) fn get_first_body_line(contents: &str) -> Option<&str> {
+    get_body(contents)
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+}
+
+/// Returns the names of the entry templates the entry was written with.
+///
+/// Besides the header and the front matter, sections added in `per-day` mode each have
+/// their own `Template:` line, in the block that starts with their `TIME:` line.  Lines
+/// of the body are never read, even if they look like one.
+pub(crate) fn extract_templates(contents: &str) -> std::collections::BTreeSet<String> {
+    let mut templates: std::collections::BTreeSet<String> =
+        get_front_matter_field(contents, TEMPLATE_FIELD)
+            .into_iter()
+            .collect();
+
+    let contents = skip_front_matter(contents);
+    let mut in_preamble = get_header_field(contents, "DATE").is_some();
+    let mut is_block_start = false;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            in_preamble = false;
+            is_block_start = true;
+            continue;
+        }
+
+        if let Some((key, value)) = trimmed.split_once(':') {
+            if is_block_start && key.trim().eq_ignore_ascii_case("TIME") {
+                in_preamble = true;
+            } else if in_preamble && key.trim().eq_ignore_ascii_case(TEMPLATE_FIELD) {
+                templates.insert(value.trim().to_string());
+            }
+        }
+        is_block_start = false;
+    }
+
+    templates.retain(|template| !template.is_empty());
+
+    templates
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn sample_weather() -> core::weather::Weather {
+        core::weather::Weather {
+            temperature: 26.0,
+            apparent_temperature: 31.7,
+            weather_code: 3,
+            rain: 0.0,
+            windspeed: 7.6,
+            // The middle of `SSW`, as only the compass direction is written
+            winddirection: 202.5,
+            pressure: 1010.3,
+            humidity: 91.0,
+            visibility: Some(11320.0),
+            uv_index: Some(8.8),
+            sunrise: "05:35".to_string(),
+            sunset: "17:59".to_string(),
+        }
+    }
+
+    /// An entry written at 23:26, i.e. at night, with a `MOOD` field and a short body
+    pub(crate) fn sample_entry(format: EntryFormat) -> Entry {
+        let datetime =
+            chrono::TimeZone::with_ymd_and_hms(&chrono_tz::Asia::Manila, 2023, 8, 22, 23, 26, 41)
+                .unwrap();
+
+        let mut entry = Entry::new(
+            &datetime,
+            "Lahug, Cebu City",
+            Some(sample_weather()),
+            format,
+        );
+        entry.fields = vec![("MOOD".to_string(), "4".to_string())];
+        entry.body = "\nWent to the market today.\n\nIt rained: a lot.\n".to_string();
+
+        entry
+    }
+
+    #[test]
+    fn plain_entry_round_trips() {
+        let entry = sample_entry(EntryFormat::Plain);
+        let contents = entry.to_string();
+
+        assert!(contents.starts_with("DATE: Tue, 2023 Aug 22 23:26:41 PST (+08:00)\n"));
+        assert_eq!(parse_entry(&contents), Ok(entry));
+    }
+
+    #[test]
+    fn front_matter_entry_round_trips() {
+        let entry = sample_entry(EntryFormat::FrontMatter);
+        let contents = entry.to_string();
+
+        assert!(contents.starts_with("+++\nschema = 1\n"));
+        assert_eq!(parse_entry(&contents), Ok(entry));
+    }
+
+    #[test]
+    fn entry_without_weather_round_trips() {
+        let mut entry = sample_entry(EntryFormat::Plain);
+        entry.weather = None;
+
+        assert_eq!(parse_entry(&entry.to_string()), Ok(entry));
+    }
+
+    #[test]
+    fn templates_are_only_read_from_preambles() {
+        let contents = "\
+DATE: Mon, 2023 Aug 21 21:30:00 PST (+08:00)
+TEMPLATE: standup
+
+Template: new budget
+
+TIME: 22:00:00 PST (+08:00)
+Template: gratitude
+
+Template: in the body again
+";
+
+        assert_eq!(
+            extract_templates(contents),
+            std::collections::BTreeSet::from(["gratitude".to_string(), "standup".to_string()])
+        );
+    }
+
+    #[test]
+    fn custom_preambles_keep_the_fields() {
+        let mut entry = sample_entry(EntryFormat::Plain);
+        entry
+            .fields
+            .push((TEMPLATE_FIELD.to_string(), "standup".to_string()));
+
+        let contents = entry
+            .to_string_with_preamble("DATE: Tuesday 23:26\nTEMPLATE: standup\n\nOvercast, 26 C\n");
+
+        assert_eq!(
+            contents,
+            "DATE: Tuesday 23:26\nTEMPLATE: standup\nMOOD: 4\n\nOvercast, 26 C\n\
+            \nWent to the market today.\n\nIt rained: a lot.\n"
+        );
+        assert_eq!(get_header_field(&contents, "mood").as_deref(), Some("4"));
+        assert_eq!(
+            extract_templates(&contents),
+            std::collections::BTreeSet::from(["standup".to_string()])
+        );
+    }
+
+    #[test]
+    fn fields_are_read_from_the_front_matter() {
+        let contents = "\
++++
+schema = 1
+date = \"2023-08-22T23:26:41+08:00\"
+timezone_abbreviation = \"PST\"
+location = \"Lahug, Cebu City\"
+
+[fields]
+MOOD = \"4\"
+TEMPLATE = \"standup\"
++++
+DATE: Tuesday 23:26
+MOOD: 2
+
+Went to the market today.
+";
+
+        // The front matter takes precedence over the header
+        assert_eq!(get_header_field(contents, "mood").as_deref(), Some("4"));
+        assert_eq!(
+            get_header_field(contents, "LOCATION").as_deref(),
+            Some("Lahug, Cebu City")
+        );
+        assert_eq!(
+            extract_templates(contents),
+            std::collections::BTreeSet::from(["standup".to_string()])
+        );
+    }
+
+    #[test]
+    fn weather_from_the_archive_has_no_uv_index_or_visibility() {
+        let contents = "\
+DATE: Mon, 2023 Aug 21 21:30:00 PST (+08:00)
+
+Visibility: n/a
+Temperature: 26 C, feels like 31.7 C, Overcast skies.
+UV Index: n/a  Sunrise: 05:35   Sunset: 17:59
+Rain: 0 mm
+Winds: 7.6 km/h SSW
+Pressure: 1010.3 hPa
+Humidity: 91%
+";
+        let weather = parse_entry(contents).unwrap().weather.unwrap();
+
+        assert_eq!(
+            weather,
+            core::weather::Weather {
+                visibility: None,
+                uv_index: None,
+                ..sample_weather()
+            }
+        );
+    }
+
+    #[test]
+    fn weather_labels_are_read_in_any_case() {
+        let contents = "\
+DATE: Mon, 2023 Aug 21 21:30:00 PST (+08:00)
+
+temperature: 26 C, feels like 31.7 C, Overcast skies.
+UV INDEX: n/a  Sunrise: 05:35   Sunset: 17:59
+rain: 0 mm
+winds: 7.6 km/h SSW
+pressure: 1010.3 hPa
+humidity: 91%
+visibility: n/a
+Went to the market today.
+";
+
+        assert!(parse_entry(contents).unwrap().weather.is_some());
+        assert_eq!(get_body(contents), "Went to the market today.\n");
+        assert_eq!(parse_entry(contents).unwrap().body, get_body(contents));
+    }
+
+    #[test]
+    fn incomplete_weather_is_reported_at_its_first_line() {
+        let contents = "\
+DATE: Mon, 2023 Aug 21 21:30:00 PST (+08:00)
+LOCATION: Lahug, Cebu City
+
+Temperature: 26 C, feels like 31.7 C, Overcast skies.
+Rain: 0 mm
+
+Went to the market today.
+";
+
+        assert_eq!(
+            parse_entry(contents),
+            Err(EntryParseError {
+                line: 4,
+                kind: EntryParseErrorKind::MissingWeatherLine("UV Index:"),
+            })
+        );
+    }
+
+    #[test]
+    fn errors_have_the_line_they_happened_on() {
+        let error_of = |contents: &str| parse_entry(contents).unwrap_err();
+
+        assert_eq!(
+            error_of("DATE: yesterday\n\nHi.\n"),
+            EntryParseError {
+                line: 1,
+                kind: EntryParseErrorKind::InvalidDate("yesterday".to_string()),
+            }
+        );
+        assert_eq!(
+            error_of("DATE: Mon, 2023 Aug 21 21:30:00 PST (+08:00)\nno colon here\n\nHi.\n").line,
+            2
+        );
+        assert_eq!(
+            error_of("LOCATION: Lahug, Cebu City\n\nHi.\n"),
+            EntryParseError {
+                line: 1,
+                kind: EntryParseErrorKind::MissingDate,
+            }
+        );
+        assert_eq!(
+            error_of(
+                "DATE: Mon, 2023 Aug 21 21:30:00 PST (+08:00)\n\
+                LOCATION: Lahug, Cebu City\n\
+                \n\
+                Temperature: 26 C, feels like 31.7 C, Overcast skies.\n\
+                Rain: lots\n"
+            ),
+            EntryParseError {
+                line: 5,
+                kind: EntryParseErrorKind::InvalidWeatherLine("Rain:"),
+            }
+        );
+    }
+
+    #[test]
+    fn front_matter_errors_have_the_line_of_the_entry() {
+        let error = parse_entry("+++\nschema = 1\ndate = yesterday\n+++\nHi.\n").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert!(matches!(
+            error.kind,
+            EntryParseErrorKind::InvalidFrontMatter(_)
+        ));
+
+        assert_eq!(
+            parse_entry("+++\nschema = 1\n\nHi.\n"),
+            Err(EntryParseError {
+                line: 1,
+                kind: EntryParseErrorKind::UnterminatedFrontMatter,
+            })
+        );
+        assert_eq!(
+            parse_entry("+++\nschema = 2\n+++\nHi.\n"),
+            Err(EntryParseError {
+                line: 1,
+                kind: EntryParseErrorKind::UnsupportedSchema(2),
+            })
+        );
+    }
+}
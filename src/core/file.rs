@@ -6,7 +6,6 @@
 use directories;
 use serde::Deserialize;
 use toml;
-use crate::core::chrono::get_current_date_from_tz_as_str;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 //
@@ -23,6 +22,7 @@ pub(crate) enum FileError {
     FailedToCreateDataDir,
     ProjDirsNotFound,
     ErrorDuringWriting(std::io::Error),
+    Encryption(crate::core::crypto::CryptoError),
 }
 
 impl From<std::io::Error> for FileError {
@@ -31,6 +31,12 @@ impl From<std::io::Error> for FileError {
     }
 }
 
+impl From<crate::core::crypto::CryptoError> for FileError {
+    fn from(error: crate::core::crypto::CryptoError) -> Self {
+        FileError::Encryption(error)
+    }
+}
+
 impl std::error::Error for FileError {}
 
 impl std::fmt::Display for FileError {
@@ -44,6 +50,7 @@ impl std::fmt::Display for FileError {
             }
             FileError::ProjDirsNotFound => write!(f, "Project directories cannot be found."),
             FileError::ErrorDuringWriting(ref err) => err.fmt(f),
+            FileError::Encryption(ref err) => err.fmt(f),
         }
     }
 }
@@ -55,6 +62,11 @@ impl std::fmt::Display for FileError {
 pub(crate) enum ConfigError {
     File(FileError),
     Toml(toml::de::Error),
+    TomlSer(toml::ser::Error),
+    TomlEdit(toml_edit::TomlError),
+    TomlEditSer(toml_edit::ser::Error),
+    /// A key of the config file that should hold a table holds something else
+    NotATable(&'static str),
 }
 
 impl From<std::io::Error> for ConfigError {
@@ -75,11 +87,37 @@ impl From<toml::de::Error> for ConfigError {
     }
 }
 
+impl From<toml::ser::Error> for ConfigError {
+    fn from(error: toml::ser::Error) -> Self {
+        ConfigError::TomlSer(error)
+    }
+}
+
+impl From<toml_edit::TomlError> for ConfigError {
+    fn from(error: toml_edit::TomlError) -> Self {
+        ConfigError::TomlEdit(error)
+    }
+}
+
+impl From<toml_edit::ser::Error> for ConfigError {
+    fn from(error: toml_edit::ser::Error) -> Self {
+        ConfigError::TomlEditSer(error)
+    }
+}
+
 impl std::fmt::Display for ConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ConfigError::File(ref err) => err.fmt(f),
             ConfigError::Toml(ref err) => err.fmt(f),
+            ConfigError::TomlSer(ref err) => err.fmt(f),
+            ConfigError::TomlEdit(ref err) => err.fmt(f),
+            ConfigError::TomlEditSer(ref err) => err.fmt(f),
+            ConfigError::NotATable(key) => write!(
+                f,
+                "`{}` in the config file is not a table.  Fix it by hand first.",
+                key
+            ),
         }
     }
 }
@@ -89,6 +127,10 @@ impl std::error::Error for ConfigError {
         match self {
             ConfigError::File(ref err) => Some(err),
             ConfigError::Toml(ref err) => Some(err),
+            ConfigError::TomlSer(ref err) => Some(err),
+            ConfigError::TomlEdit(ref err) => Some(err),
+            ConfigError::TomlEditSer(ref err) => Some(err),
+            ConfigError::NotATable(_) => None,
         }
     }
 }
@@ -107,6 +149,23 @@ pub(crate) struct ConfDefaults {
     pub(crate) location_longitude: f64,
     pub(crate) timezone: String,
     pub(crate) editor: String,
+    #[serde(default)]
+    pub(crate) entry_mode: EntryMode,
+    #[serde(default)]
+    pub(crate) entry_format: crate::core::entry::EntryFormat,
+}
+
+/// enum EntryMode
+/// How `new` lays out entries on disk
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum EntryMode {
+    /// Every `new` creates its own file (`"per-moment"`)
+    #[default]
+    PerMoment,
+    /// Every `new` on the same day adds a section to that day's file (`"per-day"`)
+    PerDay,
 }
 
 fn serde_string_as_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
@@ -117,29 +176,110 @@ where
     s.parse::<f64>().map_err(serde::de::Error::custom)
 }
 
+/// struct EntryFile
+/// A journal entry on disk, paired with the date and time encoded in its path
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone)]
+pub(crate) struct EntryFile {
+    pub(crate) datetime: chrono::NaiveDateTime,
+    pub(crate) path: std::path::PathBuf,
+}
+
+impl ConfDefaults {
+    /// Returns the default location as a `Place`
+    pub(crate) fn get_default_place(&self) -> crate::core::Place {
+        crate::core::Place {
+            full_name: self.location_full_name.clone(),
+            latitude: self.location_latitude,
+            longitude: self.location_longitude,
+            timezone: self.timezone.clone(),
+        }
+    }
+}
+
 /// struct ConfData
 /// Expected structure for the config file
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 #[derive(Debug, Deserialize)]
 pub(crate) struct ConfData {
     pub(crate) defaults: ConfDefaults,
+    /// Saved places, keyed by their alias, from the `[places.<alias>]` tables
+    #[serde(default)]
+    pub(crate) places: std::collections::BTreeMap<String, crate::core::Place>,
+    /// Other names of people mentioned in entries, e.g. `mum = "mom"` for `@mum`, from `[people]`
+    #[serde(default)]
+    pub(crate) people: std::collections::BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) tracking: ConfTracking,
+    #[serde(default)]
+    pub(crate) habits: ConfHabits,
+    #[serde(default)]
+    pub(crate) encryption: ConfEncryption,
+}
+
+/// struct ConfTracking
+/// Which values `new` asks for before opening the editor, from the `[tracking]` table
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ConfTracking {
+    #[serde(default)]
+    pub(crate) mood: bool,
+    #[serde(default)]
+    pub(crate) energy: bool,
+    #[serde(default)]
+    pub(crate) sleep: bool,
+}
+
+/// struct ConfHabits
+/// The habits that `new` adds a checklist for, from the `[habits]` table
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ConfHabits {
+    #[serde(default)]
+    pub(crate) list: Vec<String>,
+}
+
+/// struct ConfEncryption
+/// How an encrypted journal is unlocked, from the `[encryption]` table
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ConfEncryption {
+    /// A file whose contents are used instead of asking for a passphrase
+    #[serde(default)]
+    pub(crate) key_file: Option<std::path::PathBuf>,
 }
 
 // Functions that get and generate dir/file paths for the journal project
 ////////////////////////////////////////////////////////////////////////////////////////////////////
-fn get_config_file_path() -> Result<std::path::PathBuf, FileError> {
+fn get_config_dir_path() -> Result<std::path::PathBuf, FileError> {
     if let Some(proj_dirs) = directories::ProjectDirs::from("", "", env!("CARGO_PKG_NAME")) {
         let config_dir_path = proj_dirs.config_dir();
 
         std::fs::create_dir_all(&config_dir_path)
             .map_err(|_| FileError::FailedToCreateConfigDir)?;
 
-        return Ok(config_dir_path.join("config.toml"));
+        return Ok(std::path::PathBuf::from(config_dir_path));
     }
 
     Err(FileError::ProjDirsNotFound)
 }
 
+fn get_config_file_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_config_dir_path()?.join("config.toml"))
+}
+
+pub(crate) fn get_preamble_template_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_config_dir_path()?.join("preamble.txt"))
+}
+
+pub(crate) fn get_entry_templates_dir_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_config_dir_path()?.join("templates"))
+}
+
+pub(crate) fn get_prompts_file_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_config_dir_path()?.join("prompts.txt"))
+}
+
 pub fn get_data_dir_path() -> Result<std::path::PathBuf, FileError> {
     let proj_dirs =
         directories::ProjectDirs::from("", "", env!("CARGO_PKG_NAME"))
@@ -153,27 +293,142 @@ pub fn get_data_dir_path() -> Result<std::path::PathBuf, FileError> {
     Ok(std::path::PathBuf::from(data_dir_path))
 }
 
-pub(crate) fn get_temp_file_path() -> Result<std::path::PathBuf, FileError> {
-    Ok(get_data_dir_path()?.join(".temp_entry"))
+pub(crate) fn get_trash_dir_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_data_dir_path()?.join(".trash"))
+}
+
+pub(crate) fn get_recent_places_file_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_data_dir_path()?.join(".recent_places.json"))
+}
+
+/// Returns the path of the log of when each entry in the trash was deleted
+pub(crate) fn get_deletion_log_file_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_trash_dir_path()?.join(".deleted_at.json"))
+}
+
+pub(crate) fn get_index_file_path() -> Result<std::path::PathBuf, FileError> {
+    Ok(get_data_dir_path()?.join(".index.json"))
+}
+
+/// Returns the path of a review, given the name of its period, e.g. `2024-06`
+pub(crate) fn get_review_path(name: &str) -> Result<std::path::PathBuf, FileError> {
+    Ok(get_data_dir_path()?
+        .join("reviews")
+        .join(format!("{}.txt", name)))
 }
 
-pub(crate) fn get_path_for_todays_entry() -> Result<String, Box<dyn std::error::Error>>{
+pub(crate) fn get_path_for_entry(
+    date: &chrono::DateTime<chrono_tz::Tz>,
+) -> Result<std::path::PathBuf, FileError> {
     let extension = "txt";
 
     let data_dir = get_data_dir_path()?;
 
-    let conf_data = get_config_from_config_file()?.defaults;
+    Ok(data_dir.join(format!("{}.{}", date.format("%Y/%m/%d.%H-%M"), extension)))
+}
 
-    let current_date = get_current_date_from_tz_as_str(&conf_data.timezone)?;
+/// Parses the date and time encoded in an entry's path relative to the data directory.
+///
+/// # Arguments
+///
+/// * `filename` - The entry's path relative to the data directory, which should
+///   have the format `YYYY/MM/DD.HH-mm.txt`.
+pub(crate) fn extract_naive_datetime(
+    filename: &std::path::Path,
+) -> chrono::ParseResult<chrono::NaiveDateTime> {
+    // Convert PathBuf to string with forward slashes
+    let path_string = filename
+        .with_extension("")
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    chrono::NaiveDateTime::parse_from_str(&path_string, "%Y/%m/%d.%H-%M")
+}
 
-    let todays_entry_path = format!(
-        "{}/{}.{}",
-        data_dir.to_string_lossy(),
-        current_date.format("%Y/%m/%d.%H-%M"),
-        extension
-    );
+/// Returns the `EntryFile` for an entry's absolute path, if it follows the `YYYY/MM/DD.HH-mm.txt` layout
+pub(crate) fn get_entry_file(path: &std::path::Path) -> Result<Option<EntryFile>, FileError> {
+    let data_dir = get_data_dir_path()?;
+
+    Ok(path
+        .strip_prefix(&data_dir)
+        .ok()
+        .and_then(|relative_path| extract_naive_datetime(relative_path).ok())
+        .map(|datetime| EntryFile {
+            datetime,
+            path: path.to_path_buf(),
+        }))
+}
 
-    Ok(todays_entry_path)
+/// Returns every entry in the data directory, sorted from oldest to newest.
+///
+/// Files whose paths do not follow the `YYYY/MM/DD.HH-mm.txt` layout are skipped.
+pub(crate) fn get_all_entries() -> Result<Vec<EntryFile>, FileError> {
+    get_entries_in_dir(&get_data_dir_path()?)
+}
+
+/// Returns every entry under `dir`, laid out as `YYYY/MM/DD.HH-mm.txt`, sorted from oldest to newest.
+///
+/// Hidden files and directories (e.g. the trash) are skipped.
+pub(crate) fn get_entries_in_dir(dir: &std::path::Path) -> Result<Vec<EntryFile>, FileError> {
+    let mut entries: Vec<EntryFile> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative_path = e.path().strip_prefix(dir).ok()?;
+            let datetime = extract_naive_datetime(relative_path).ok()?;
+            Some(EntryFile {
+                datetime,
+                path: e.path().to_path_buf(),
+            })
+        })
+        .collect();
+
+    entries.sort_by_key(|entry| entry.datetime);
+
+    Ok(entries)
+}
+
+/// Returns every entry written on `month`/`day` of any year, sorted from oldest to newest.
+///
+/// Only the `YYYY/MM` directories of that month are read, not the whole data directory.
+pub(crate) fn get_entries_on_day_of_year(
+    month: u32,
+    day: u32,
+) -> Result<Vec<EntryFile>, FileError> {
+    let data_dir = get_data_dir_path()?;
+    let day_prefix = format!("{:02}.", day);
+
+    let mut entries: Vec<EntryFile> = vec![];
+    for year_dir in std::fs::read_dir(&data_dir)?.filter_map(|e| e.ok()) {
+        // Year directories are the only ones named by a number, which also skips the hidden ones
+        if year_dir
+            .file_name()
+            .to_string_lossy()
+            .parse::<i32>()
+            .is_err()
+        {
+            continue;
+        }
+
+        let month_dir = year_dir.path().join(format!("{:02}", month));
+        let Ok(files) = std::fs::read_dir(&month_dir) else {
+            continue;
+        };
+        for file in files.filter_map(|e| e.ok()) {
+            if !file.file_name().to_string_lossy().starts_with(&day_prefix) {
+                continue;
+            }
+            if let Some(entry) = get_entry_file(&file.path())? {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| entry.datetime);
+
+    Ok(entries)
 }
 
 // If X exists checkers
@@ -202,3 +457,284 @@ pub(crate) fn get_config_from_config_file() -> Result<ConfData, ConfigError> {
 
     Ok(conf_data)
 }
+
+/// Returns the contents of the user's preamble template, or `None` if they have not written one
+pub(crate) fn get_preamble_template() -> Result<Option<String>, FileError> {
+    match std::fs::read_to_string(get_preamble_template_path()?) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Returns the contents of the entry template `templates/<name>.txt`, or `None` if there is none
+pub(crate) fn get_entry_template(name: &str) -> Result<Option<String>, FileError> {
+    // Names are only ever file names in `templates/`
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Ok(None);
+    }
+
+    let path = get_entry_templates_dir_path()?.join(format!("{}.txt", name));
+
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Returns the names of the entry templates in `templates/`, sorted
+pub(crate) fn get_entry_template_names() -> Result<Vec<String>, FileError> {
+    let dir = get_entry_templates_dir_path()?;
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let mut names: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|dir_entry| dir_entry.ok())
+        .map(|dir_entry| dir_entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+
+    Ok(names)
+}
+
+/// Returns the user's writing prompts: every line of `prompts.txt` that is not blank or a `#` comment
+pub(crate) fn get_prompts() -> Result<Vec<String>, FileError> {
+    let contents = match std::fs::read_to_string(get_prompts_file_path()?) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => return Err(error.into()),
+    };
+
+    Ok(contents
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Reads a file of the journal, decrypting it if it is encrypted
+pub(crate) fn read_journal_file(
+    path: &std::path::Path,
+) -> Result<zeroize::Zeroizing<Vec<u8>>, FileError> {
+    decrypt_journal_contents(std::fs::read(path)?)
+}
+
+/// Decrypts the contents of a file of the journal, e.g. as kept in its history, if they are encrypted
+pub(crate) fn decrypt_journal_contents(
+    bytes: Vec<u8>,
+) -> Result<zeroize::Zeroizing<Vec<u8>>, FileError> {
+    if !crate::core::crypto::is_encrypted(&bytes) {
+        return Ok(zeroize::Zeroizing::new(bytes));
+    }
+
+    Ok(crate::core::crypto::decrypt(
+        crate::core::crypto::get_key()?,
+        &bytes,
+    )?)
+}
+
+/// Reads a file of the journal like `read_journal_file`, or returns `None` if it does not exist
+pub(crate) fn read_journal_file_if_exists(
+    path: &std::path::Path,
+) -> Result<Option<zeroize::Zeroizing<Vec<u8>>>, FileError> {
+    match read_journal_file(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(FileError::ErrorDuringWriting(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes a file of the journal, encrypting it if the journal is encrypted
+pub(crate) fn write_journal_file(path: &std::path::Path, contents: &[u8]) -> Result<(), FileError> {
+    if crate::core::crypto::is_journal_encrypted()? {
+        let encrypted = crate::core::crypto::encrypt(crate::core::crypto::get_key()?, contents)?;
+        write_file_atomically(path, &encrypted)
+    } else {
+        write_file_atomically(path, contents)
+    }
+}
+
+fn write_file_atomically(path: &std::path::Path, contents: &[u8]) -> Result<(), FileError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Write to a hidden sibling file first so a crash never leaves a half-written file behind
+    let file_name = path
+        .file_name()
+        .map_or(String::new(), |name| name.to_string_lossy().to_string());
+    let partial_path = path.with_file_name(format!(".{}.partial", file_name));
+    std::fs::write(&partial_path, contents)?;
+    std::fs::rename(&partial_path, path)?;
+
+    Ok(())
+}
+
+pub(crate) fn read_entry(path: &std::path::Path) -> Result<String, FileError> {
+    let contents = read_journal_file(path)?;
+
+    Ok(std::str::from_utf8(&contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        .to_string())
+}
+
+pub(crate) fn write_entry(path: &std::path::Path, contents: &str) -> Result<(), FileError> {
+    write_journal_file(path, contents.as_bytes())
+}
+
+/// Encrypts or decrypts a file of the journal in place, with the journal's key.
+///
+/// # Returns
+///
+/// Whether the file was changed, i.e. `false` if it already was as asked
+pub(crate) fn set_file_encryption(
+    path: &std::path::Path,
+    is_to_encrypt: bool,
+) -> Result<bool, FileError> {
+    let bytes = std::fs::read(path)?;
+    if crate::core::crypto::is_encrypted(&bytes) == is_to_encrypt {
+        return Ok(false);
+    }
+
+    let key = crate::core::crypto::get_key()?;
+    if is_to_encrypt {
+        write_file_atomically(path, &crate::core::crypto::encrypt(key, &bytes)?)?;
+    } else {
+        write_file_atomically(path, &crate::core::crypto::decrypt(key, &bytes)?)?;
+    }
+
+    Ok(true)
+}
+
+/// Returns every file of the journal that holds what the user wrote: the entries,
+/// the entries in the trash, and the reviews
+pub(crate) fn get_journal_files() -> Result<Vec<std::path::PathBuf>, FileError> {
+    let mut paths: Vec<std::path::PathBuf> = get_all_entries()?
+        .into_iter()
+        .chain(get_entries_in_dir(&get_trash_dir_path()?)?)
+        .map(|entry| entry.path)
+        .collect();
+
+    if let Ok(reviews) = std::fs::read_dir(get_data_dir_path()?.join("reviews")) {
+        paths.extend(
+            reviews
+                .filter_map(|e| e.ok())
+                .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+                .map(|e| e.path())
+                .filter(|path| path.is_file()),
+        );
+    }
+
+    // They tell where the user has been and what they deleted when
+    paths.extend(
+        [
+            get_recent_places_file_path()?,
+            get_deletion_log_file_path()?,
+        ]
+        .into_iter()
+        .filter(|path| path.is_file()),
+    );
+
+    Ok(paths)
+}
+
+/// How many ad-hoc locations `new --recent` remembers
+const RECENT_PLACES_LIMIT: usize = 10;
+
+/// Returns the ad-hoc locations entries were recently written from, most recent first
+pub(crate) fn get_recent_places() -> Result<Vec<crate::core::Place>, FileError> {
+    let recent_places = read_journal_file_if_exists(&get_recent_places_file_path()?)?
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    Ok(recent_places)
+}
+
+/// Moves `place` to the top of the recent locations, forgetting the oldest ones over the limit
+pub(crate) fn remember_recent_place(place: &crate::core::Place) -> Result<(), FileError> {
+    let mut recent_places = get_recent_places()?;
+
+    recent_places.retain(|recent_place| recent_place != place);
+    recent_places.insert(0, place.clone());
+    recent_places.truncate(RECENT_PLACES_LIMIT);
+
+    let contents = serde_json::to_vec_pretty(&recent_places)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    write_journal_file(&get_recent_places_file_path()?, &contents)?;
+
+    Ok(())
+}
+
+/// Reads the config file as a TOML document, for changing parts of it.  Unlike a plain
+/// table, the document keeps the file's comments and layout when written back.
+fn get_config_document_from_config_file() -> Result<toml_edit::Document, ConfigError> {
+    let contents = std::fs::read_to_string(get_config_file_path()?)?;
+
+    Ok(contents.parse()?)
+}
+
+fn write_config_document_to_config_file(
+    config_document: &toml_edit::Document,
+) -> Result<(), ConfigError> {
+    write_contents_to_config_file(config_document.to_string())?;
+
+    Ok(())
+}
+
+/// Saves a place under `[places.<alias>]`, replacing any place with the same alias
+pub(crate) fn add_place_to_config_file(
+    alias: &str,
+    place: &crate::core::Place,
+) -> Result<(), ConfigError> {
+    let mut config_document = get_config_document_from_config_file()?;
+
+    let places = config_document.entry("places").or_insert_with(|| {
+        // Only the `[places.<alias>]` headers are written, not an empty `[places]`
+        let mut places = toml_edit::Table::new();
+        places.set_implicit(true);
+        toml_edit::Item::Table(places)
+    });
+    let places = places
+        .as_table_mut()
+        .ok_or(ConfigError::NotATable("places"))?;
+
+    let place_document = toml_edit::ser::to_document(place)?;
+    places.insert(
+        alias,
+        toml_edit::Item::Table(place_document.as_table().clone()),
+    );
+
+    write_config_document_to_config_file(&config_document)
+}
+
+/// Removes the place saved under `[places.<alias>]`.
+///
+/// # Returns
+///
+/// `false` if there was no place with that alias.
+pub(crate) fn remove_place_from_config_file(alias: &str) -> Result<bool, ConfigError> {
+    let mut config_document = get_config_document_from_config_file()?;
+
+    let was_removed = match config_document.get_mut("places") {
+        Some(places) => places
+            .as_table_like_mut()
+            .ok_or(ConfigError::NotATable("places"))?
+            .remove(alias)
+            .is_some(),
+        None => false,
+    };
+
+    if was_removed {
+        write_config_document_to_config_file(&config_document)?;
+    }
+
+    Ok(was_removed)
+}
@@ -0,0 +1,104 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the habit checklists that `new` adds to entries
+//!
+//! A checklist is a line per habit, `[ ] exercise`, that the user ticks as
+//! `[x] exercise`.  A habit is done on a day if it is ticked in any of that
+//! day's entries.
+
+use std::collections::BTreeMap;
+
+use crate::core;
+
+/// Line above the checklist
+const CHECKLIST_TITLE: &str = "Habits:";
+
+/// Returns the checklist of `habits`, none of them ticked
+pub(crate) fn generate_checklist(habits: &[String]) -> String {
+    let mut checklist = format!("{}\n", CHECKLIST_TITLE);
+    for habit in habits {
+        checklist.push_str(&format!("[ ] {}\n", habit));
+    }
+
+    checklist
+}
+
+/// Returns every `[ ] habit` and `[x] habit` line in `text`, with whether it is ticked.
+///
+/// Habits are lowercased.  A `- ` before the box, as in Markdown, is allowed.
+pub(crate) fn parse_checklist(text: &str) -> BTreeMap<String, bool> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("- ").unwrap_or(line);
+
+            let is_done = match line.get(..3)? {
+                "[ ]" => false,
+                "[x]" | "[X]" => true,
+                _ => return None,
+            };
+            let habit = line[3..].trim();
+
+            (!habit.is_empty()).then(|| (habit.to_lowercase(), is_done))
+        })
+        .collect()
+}
+
+/// Returns, for each day with a checklist that has `habit`, whether it was done
+pub(crate) fn get_habit_days(
+    index: &core::index::JournalIndex,
+    habit: &str,
+) -> BTreeMap<chrono::NaiveDate, bool> {
+    let habit = habit.to_lowercase();
+    let mut days: BTreeMap<chrono::NaiveDate, bool> = BTreeMap::new();
+
+    for entry in index.entries.values() {
+        if let Some(is_done) = entry.habits.get(&habit) {
+            *days.entry(entry.datetime.date()).or_default() |= is_done;
+        }
+    }
+
+    days
+}
+
+/// struct HabitSummary
+/// How well a habit was kept
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HabitSummary {
+    /// Days with a checklist that has the habit
+    pub(crate) tracked_days: usize,
+    pub(crate) done_days: usize,
+    /// Days in a row up to today, or up to yesterday if it is not done yet today
+    pub(crate) current_streak: usize,
+    pub(crate) longest_streak: usize,
+}
+
+impl HabitSummary {
+    pub(crate) fn get_completion_rate(&self) -> Option<f64> {
+        (self.tracked_days > 0).then(|| self.done_days as f64 / self.tracked_days as f64)
+    }
+}
+
+/// Summarises the days returned by `get_habit_days`
+pub(crate) fn get_habit_summary(
+    days: &BTreeMap<chrono::NaiveDate, bool>,
+    today: chrono::NaiveDate,
+) -> HabitSummary {
+    let done_days: std::collections::BTreeSet<chrono::NaiveDate> = days
+        .iter()
+        .filter(|(_, is_done)| **is_done)
+        .map(|(day, _)| *day)
+        .collect();
+    let (current_streak, longest_streak) = core::chrono::get_streaks(&done_days, today);
+
+    HabitSummary {
+        tracked_days: days.len(),
+        done_days: done_days.len(),
+        current_streak,
+        longest_streak,
+    }
+}
@@ -9,10 +9,23 @@
 ///
 /// * `degrees` - The degrees to convert to a cardinal direction. Should be between 0 and 360.
 pub(crate) fn get_direction(degrees: f64) -> String {
-    let directions = [
-        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
-        "NW", "NNW",
-    ];
     let index = ((degrees + 11.25) / 22.5) as usize % 16;
-    return String::from(directions[index]);
+    return String::from(DIRECTIONS[index]);
 }
+
+/// Returns the degrees in the middle of a cardinal direction, the reverse of `get_direction`.
+///
+/// # Arguments
+///
+/// * `direction` - The cardinal direction, e.g. `SSW`.  Matching is case-insensitive.
+pub(crate) fn get_degrees(direction: &str) -> Option<f64> {
+    DIRECTIONS
+        .iter()
+        .position(|known_direction| known_direction.eq_ignore_ascii_case(direction))
+        .map(|index| index as f64 * 22.5)
+}
+
+const DIRECTIONS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
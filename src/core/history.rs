@@ -0,0 +1,362 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the history of the journal, kept by making the data directory a git repository
+//!
+//! History is on when the data directory has a `.git` directory.  Every
+//! command that changes the journal then commits all of its changes, so that
+//! any earlier version of an entry can be got back.  The `git` binary is run
+//! for all of this, as the user may well want to use it on the history too.
+
+use crate::core;
+
+/// Files that are kept out of the history, as they can be rebuilt or are only needed for a while
+const GITIGNORE: &str = "\
+.index.json
+.recent_places.json
+*.partial
+";
+
+/// Separates the fields of each revision in `git log`'s output
+const FIELD_SEPARATOR: char = '\t';
+
+/// enum HistoryError
+/// Represents errors that can occur while keeping the journal's history
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug)]
+pub(crate) enum HistoryError {
+    File(core::file::FileError),
+    GitNotFound,
+    /// git failed, with what it printed to stderr
+    Git(String),
+    InvalidRevision(String),
+}
+
+impl From<std::io::Error> for HistoryError {
+    fn from(error: std::io::Error) -> Self {
+        HistoryError::File(core::file::FileError::ErrorDuringWriting(error))
+    }
+}
+
+impl From<core::file::FileError> for HistoryError {
+    fn from(error: core::file::FileError) -> Self {
+        HistoryError::File(error)
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+impl std::fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryError::File(ref err) => err.fmt(f),
+            HistoryError::GitNotFound => {
+                write!(
+                    f,
+                    "git cannot be found.  Install it to keep your journal's history."
+                )
+            }
+            HistoryError::Git(ref stderr) => write!(f, "git failed: {}", stderr),
+            HistoryError::InvalidRevision(ref revision) => write!(
+                f,
+                "'{}' is not a revision.  Run `journey2 history` to list them.",
+                revision
+            ),
+        }
+    }
+}
+
+/// struct Revision
+/// A commit of the journal's history
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone)]
+pub(crate) struct Revision {
+    /// The commit's abbreviated hash
+    pub(crate) hash: String,
+    pub(crate) datetime: chrono::DateTime<chrono::FixedOffset>,
+    pub(crate) message: String,
+}
+
+/// Runs git in the data directory, whether or not it succeeds
+fn run_git(args: &[&str]) -> Result<std::process::Output, HistoryError> {
+    std::process::Command::new("git")
+        .arg("-C")
+        .arg(core::file::get_data_dir_path()?)
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => HistoryError::GitNotFound,
+            _ => e.into(),
+        })
+}
+
+/// Runs git in the data directory, and returns what it printed if it succeeded
+fn run_git_checked(args: &[&str]) -> Result<String, HistoryError> {
+    let output = run_git(args)?;
+
+    if !output.status.success() {
+        return Err(HistoryError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub(crate) fn is_history_enabled() -> Result<bool, core::file::FileError> {
+    Ok(core::file::get_data_dir_path()?.join(".git").exists())
+}
+
+/// Returns whether anything has been committed yet
+fn has_revisions() -> Result<bool, HistoryError> {
+    Ok(run_git(&["rev-parse", "--verify", "--quiet", "HEAD"])?
+        .status
+        .success())
+}
+
+/// Makes the data directory a git repository, and commits the journal as it is.
+///
+/// # Returns
+///
+/// Whether history was turned on, i.e. `false` if it already was
+pub(crate) fn enable_history() -> Result<bool, HistoryError> {
+    if is_history_enabled()? {
+        return Ok(false);
+    }
+
+    run_git_checked(&["init", "--quiet"])?;
+
+    let gitignore_path = core::file::get_data_dir_path()?.join(".gitignore");
+    if !gitignore_path.exists() {
+        std::fs::write(gitignore_path, GITIGNORE)?;
+    }
+
+    // Commits need an author, which the user may not have set up for git
+    if !run_git(&["config", "user.email"])?.status.success() {
+        run_git_checked(&["config", "user.name", env!("CARGO_PKG_NAME")])?;
+        run_git_checked(&["config", "user.email", "journey2@localhost"])?;
+    }
+
+    commit_changes("start history")?;
+
+    Ok(true)
+}
+
+/// Deletes the history and starts it over from the journal as it is, e.g. so that no
+/// earlier revision is left unencrypted once the journal has been encrypted.
+pub(crate) fn restart_history() -> Result<(), HistoryError> {
+    let git_dir_path = core::file::get_data_dir_path()?.join(".git");
+    if git_dir_path.exists() {
+        std::fs::remove_dir_all(git_dir_path)?;
+    }
+
+    enable_history()?;
+
+    Ok(())
+}
+
+/// Commits every change to the journal, if it has a history.
+///
+/// # Returns
+///
+/// Whether a commit was made, i.e. `false` if there is no history or nothing changed
+pub(crate) fn commit_changes(message: &str) -> Result<bool, HistoryError> {
+    if !is_history_enabled()? {
+        return Ok(false);
+    }
+
+    run_git_checked(&["add", "--all"])?;
+
+    // Exits successfully only if nothing is staged
+    if run_git(&["diff", "--cached", "--quiet"])?.status.success() {
+        return Ok(false);
+    }
+
+    run_git_checked(&["commit", "--quiet", "--message", message])?;
+
+    Ok(true)
+}
+
+/// Returns the revisions of the entry at `path`, or of the whole journal if `None`, newest first
+pub(crate) fn get_revisions(path: Option<&std::path::Path>) -> Result<Vec<Revision>, HistoryError> {
+    if !has_revisions()? {
+        return Ok(vec![]);
+    }
+
+    let key = path.map(core::index::get_index_key);
+    let mut args = vec!["log", "--format=%h%x09%aI%x09%s"];
+    if let Some(key) = key.as_deref() {
+        // Follows the entry into and out of the trash
+        args.extend(["--follow", "--", key]);
+    }
+
+    Ok(run_git_checked(&args)?
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, FIELD_SEPARATOR);
+            Some(Revision {
+                hash: fields.next()?.to_string(),
+                datetime: chrono::DateTime::parse_from_rfc3339(fields.next()?).ok()?,
+                message: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Returns the changes of a revision, as a diff.
+///
+/// # Arguments
+///
+/// * `path` - Only show the changes to this entry.  The revision is then the last one, at or
+///   before `revision`, that changed it.
+/// * `revision` - Defaults to the most recent one
+pub(crate) fn get_diff(
+    path: Option<&std::path::Path>,
+    revision: Option<&str>,
+) -> Result<String, HistoryError> {
+    if !has_revisions()? {
+        return Ok(String::new());
+    }
+
+    if let Some(revision) = revision {
+        // Anything starting with '-' would be taken as an option
+        if revision.starts_with('-')
+            || !run_git(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^{{commit}}", revision),
+            ])?
+            .status
+            .success()
+        {
+            return Err(HistoryError::InvalidRevision(revision.to_string()));
+        }
+    }
+
+    let key = path.map(core::index::get_index_key);
+    let mut selection: Vec<&str> = revision.into_iter().collect();
+    if let Some(key) = key.as_deref() {
+        selection.extend(["--follow", "--", key]);
+    }
+
+    // git can only show encrypted files as binary, so they are decrypted and compared here
+    let is_encrypted = core::crypto::is_journal_encrypted()?;
+    let mut args = vec![
+        "log",
+        "--max-count=1",
+        "--no-color",
+        "--date=format:%a, %Y %b %d %H:%M:%S",
+        "--format=revision %h, %ad%n%n    %s",
+    ];
+    if !is_encrypted {
+        args.push("--patch");
+    }
+    args.extend(&selection);
+
+    let log = run_git_checked(&args)?;
+    if !is_encrypted || log.is_empty() {
+        return Ok(log);
+    }
+
+    let mut args = vec!["log", "--max-count=1", "--format=%H"];
+    args.extend(&selection);
+    let hash = run_git_checked(&args)?.trim().to_string();
+
+    Ok(format!(
+        "{}\n{}",
+        log,
+        get_decrypted_diff(&hash, key.as_deref())?
+    ))
+}
+
+/// Returns the changes of the revision `hash`, decrypted, as a unified diff
+///
+/// # Arguments
+///
+/// * `key` - Only show the changes to the entry with this index key, in or out of the trash
+fn get_decrypted_diff(hash: &str, key: Option<&str>) -> Result<String, HistoryError> {
+    // --root lists the files of the first revision as added
+    let changes = run_git_checked(&[
+        "diff-tree",
+        "-r",
+        "--root",
+        "--no-commit-id",
+        "-M",
+        "--name-status",
+        "-z",
+        hash,
+    ])?;
+
+    let mut fields = changes.split('\0').filter(|field| !field.is_empty());
+    let mut diff = String::new();
+    while let Some(status) = fields.next() {
+        // Renames and copies have both the old and the new path
+        let (old_path, new_path) = match status.chars().next() {
+            Some('A') => (None, fields.next()),
+            Some('D') => (fields.next(), None),
+            Some('R') | Some('C') => (fields.next(), fields.next()),
+            _ => {
+                let path = fields.next();
+                (path, path)
+            }
+        };
+
+        let is_of_key = |path: Option<&str>| {
+            path.is_some_and(|path| Some(path.trim_start_matches(".trash/")) == key)
+        };
+        if key.is_some() && !is_of_key(old_path) && !is_of_key(new_path) {
+            continue;
+        }
+
+        let old_contents = match old_path {
+            Some(path) => get_file_at_revision(&format!("{}^", hash), path)?,
+            None => zeroize::Zeroizing::new(vec![]),
+        };
+        let new_contents = match new_path {
+            Some(path) => get_file_at_revision(hash, path)?,
+            None => zeroize::Zeroizing::new(vec![]),
+        };
+        // e.g. when the journal was encrypted, which changes the files but not what they say
+        if old_contents == new_contents && old_path == new_path {
+            continue;
+        }
+
+        let old_label = old_path.map_or("/dev/null".to_string(), |path| format!("a/{}", path));
+        let new_label = new_path.map_or("/dev/null".to_string(), |path| format!("b/{}", path));
+        let old_text = String::from_utf8_lossy(&old_contents);
+        let new_text = String::from_utf8_lossy(&new_contents);
+
+        diff.push_str(&format!(
+            "diff --git a/{} b/{}\n",
+            old_path.or(new_path).unwrap_or_default(),
+            new_path.or(old_path).unwrap_or_default()
+        ));
+        diff.push_str(
+            &similar::TextDiff::from_lines(old_text.as_ref(), new_text.as_ref())
+                .unified_diff()
+                .header(&old_label, &new_label)
+                .to_string(),
+        );
+    }
+
+    Ok(diff)
+}
+
+/// Returns the contents of the file at `path` as of `revision`, decrypted
+fn get_file_at_revision(
+    revision: &str,
+    path: &str,
+) -> Result<zeroize::Zeroizing<Vec<u8>>, HistoryError> {
+    let output = run_git(&["show", &format!("{}:{}", revision, path)])?;
+
+    if !output.status.success() {
+        return Err(HistoryError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(core::file::decrypt_journal_contents(output.stdout)?)
+}
@@ -0,0 +1,481 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the on-disk index of the journal
+//!
+//! The index lets commands look up entries and their details without walking
+//! the data directory and reading every entry.  It is kept up to date as
+//! entries are written, and the `YYYY/MM` directories whose files were added,
+//! removed or replaced by other means, e.g. by syncing, are read again when the
+//! index is loaded.  It can also be rebuilt from scratch with `reindex`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core;
+
+/// Bump this when the layout of `JournalIndex` changes so old indices get rebuilt
+const INDEX_VERSION: u32 = 7;
+
+/// enum IndexError
+/// Wrapper for all the errors that can occur while reading or writing the index
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug)]
+pub(crate) enum IndexError {
+    File(core::file::FileError),
+    SerdeJson(serde_json::Error),
+}
+
+impl From<std::io::Error> for IndexError {
+    fn from(error: std::io::Error) -> Self {
+        IndexError::File(core::file::FileError::ErrorDuringWriting(error))
+    }
+}
+
+impl From<core::file::FileError> for IndexError {
+    fn from(error: core::file::FileError) -> Self {
+        IndexError::File(error)
+    }
+}
+
+impl From<serde_json::Error> for IndexError {
+    fn from(error: serde_json::Error) -> Self {
+        IndexError::SerdeJson(error)
+    }
+}
+
+impl std::fmt::Display for IndexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexError::File(ref err) => err.fmt(f),
+            IndexError::SerdeJson(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IndexError::File(ref err) => Some(err),
+            IndexError::SerdeJson(ref err) => Some(err),
+        }
+    }
+}
+
+/// struct IndexedEntry
+/// Everything the index knows about a single entry
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub(crate) struct IndexedEntry {
+    pub(crate) datetime: chrono::NaiveDateTime,
+    pub(crate) location: Option<String>,
+    pub(crate) weather: Option<core::weather::Weather>,
+    /// The first line of the body
+    pub(crate) summary: Option<String>,
+    pub(crate) word_count: usize,
+    /// From both the body and the `TAGS:` line, see `core::tags`
+    pub(crate) tags: BTreeSet<String>,
+    /// How many times each `@name` is mentioned, as written, i.e. before resolving aliases
+    pub(crate) mentions: BTreeMap<String, usize>,
+    /// Mood, energy and sleep, if `new` asked for them
+    pub(crate) tracking: core::tracking::Tracking,
+    /// The entry templates the entry was written with, see `new --template`
+    pub(crate) templates: BTreeSet<String>,
+    /// Whether each habit in the entry's checklist was ticked, see `core::habits`
+    pub(crate) habits: BTreeMap<String, bool>,
+    /// Why the entry, or its preamble, could not be read, if it could not
+    pub(crate) parse_error: Option<String>,
+    /// When the entry's file was last modified, to tell whether it changed since it was indexed
+    pub(crate) modified: Option<std::time::SystemTime>,
+}
+
+/// struct JournalIndex
+/// The index of the whole journal, keyed by each entry's path relative to the data directory
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub(crate) struct JournalIndex {
+    version: u32,
+    pub(crate) entries: BTreeMap<String, IndexedEntry>,
+    /// Inverted index from each (lowercased) word of the whole entry, preamble included,
+    /// to the entries that contain it
+    pub(crate) terms: BTreeMap<String, BTreeSet<String>>,
+    /// When each `YYYY/MM` directory was last modified when it was indexed, keyed by its path
+    /// relative to the data directory
+    dirs_modified: BTreeMap<String, std::time::SystemTime>,
+}
+
+impl JournalIndex {
+    /// Adds an entry to the index, replacing whatever was indexed for it before
+    pub(crate) fn insert(&mut self, entry: &core::file::EntryFile, contents: &str) {
+        let key = get_index_key(&entry.path);
+
+        self.remove(&key);
+
+        // Entries that cannot be parsed are still indexed, with whatever can be read from them
+        let (location, weather, body, parse_error) = match core::entry::parse_entry(contents) {
+            Ok(parsed) => (parsed.location, parsed.weather, parsed.body, None),
+            Err(e) => (
+                core::entry::get_header_field(contents, "LOCATION"),
+                None,
+                core::entry::get_body(contents).to_string(),
+                Some(e.to_string()),
+            ),
+        };
+
+        // The preamble is searched too, e.g. for a location or the weather
+        for term in get_terms(contents) {
+            self.terms.entry(term).or_default().insert(key.clone());
+        }
+
+        self.entries.insert(
+            key,
+            IndexedEntry {
+                datetime: entry.datetime,
+                location,
+                weather,
+                summary: body
+                    .lines()
+                    .map(|line| line.trim())
+                    .find(|line| !line.is_empty())
+                    .map(str::to_string),
+                word_count: body.split_whitespace().count(),
+                tags: core::tags::get_tags(contents),
+                mentions: core::people::extract_mentions(&body),
+                tracking: core::tracking::Tracking::from_contents(contents),
+                templates: core::entry::extract_templates(contents),
+                habits: core::habits::parse_checklist(&body),
+                parse_error,
+                modified: get_modified_time(&entry.path),
+            },
+        );
+    }
+
+    /// Reads an entry from disk and adds it to the index.  An entry that cannot be read, e.g. as
+    /// it is not valid UTF-8, is still indexed, with why in its `parse_error`, so that it does not
+    /// stop every command.
+    ///
+    /// # Returns
+    ///
+    /// Whether the entry could be read
+    fn insert_from_file(&mut self, entry: &core::file::EntryFile) -> bool {
+        match core::file::read_entry(&entry.path) {
+            Ok(contents) => {
+                self.insert(entry, &contents);
+                true
+            }
+            Err(e) => {
+                let key = get_index_key(&entry.path);
+                self.remove(&key);
+                // Without `modified`, it is read again whenever its directory is
+                self.entries.insert(
+                    key,
+                    IndexedEntry {
+                        datetime: entry.datetime,
+                        parse_error: Some(e.to_string()),
+                        ..Default::default()
+                    },
+                );
+                false
+            }
+        }
+    }
+
+    /// Removes an entry, given its key, from the index
+    pub(crate) fn remove(&mut self, key: &str) {
+        if self.entries.remove(key).is_none() {
+            return;
+        }
+
+        self.terms.retain(|_, keys| {
+            keys.remove(key);
+            !keys.is_empty()
+        });
+    }
+
+    /// Reads again the `YYYY/MM` directories that were modified since they were indexed, e.g.
+    /// after syncing from elsewhere, and forgets the entries of those that are gone.  Only the
+    /// directories are looked at otherwise, so an entry edited in place by other means, which
+    /// leaves its directory as it was, is only read again by `reindex`.
+    ///
+    /// # Returns
+    ///
+    /// Whether anything changed
+    fn refresh(&mut self) -> Result<bool, IndexError> {
+        let data_dir = core::file::get_data_dir_path()?;
+        let month_dirs = get_month_dirs(&data_dir);
+        let mut is_changed = false;
+
+        let removed_keys: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|key| !month_dirs.contains_key(get_month_dir_key(key)))
+            .cloned()
+            .collect();
+        for key in removed_keys {
+            self.remove(&key);
+            is_changed = true;
+        }
+        self.dirs_modified
+            .retain(|dir_key, _| month_dirs.contains_key(dir_key));
+
+        for (dir_key, modified) in month_dirs {
+            if modified.is_some() && self.dirs_modified.get(&dir_key) == modified.as_ref() {
+                continue;
+            }
+
+            self.dirs_modified.remove(&dir_key);
+            is_changed |= self.refresh_dir(&data_dir, &dir_key)?;
+        }
+
+        Ok(is_changed)
+    }
+
+    /// Reads again the entries of a `YYYY/MM` directory that were added or changed since they
+    /// were indexed, and forgets those that are gone.  The directory is remembered as up to
+    /// date unless one of its entries could not be read, so that it is tried again next time.
+    ///
+    /// # Returns
+    ///
+    /// Whether anything changed
+    fn refresh_dir(
+        &mut self,
+        data_dir: &std::path::Path,
+        dir_key: &str,
+    ) -> Result<bool, IndexError> {
+        let dir = data_dir.join(dir_key);
+        let modified = get_modified_time(&dir);
+        let mut is_changed = false;
+        let mut is_readable = true;
+        let mut keys_on_disk: BTreeSet<String> = BTreeSet::new();
+
+        for dir_entry in std::fs::read_dir(&dir)? {
+            let file_name = dir_entry?.file_name().to_string_lossy().to_string();
+            let key = format!("{}/{}", dir_key, file_name);
+            let Ok(datetime) = core::file::extract_naive_datetime(std::path::Path::new(&key))
+            else {
+                continue;
+            };
+            let entry = core::file::EntryFile {
+                datetime,
+                path: dir.join(&file_name),
+            };
+            let file_modified = get_modified_time(&entry.path);
+
+            let is_up_to_date = self.entries.get(&key).is_some_and(|indexed| {
+                file_modified.is_some() && indexed.modified == file_modified
+            });
+            if !is_up_to_date {
+                is_readable &= self.insert_from_file(&entry);
+                is_changed = true;
+            }
+
+            keys_on_disk.insert(key);
+        }
+
+        let removed_keys: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|key| get_month_dir_key(key) == dir_key && !keys_on_disk.contains(*key))
+            .cloned()
+            .collect();
+        for key in removed_keys {
+            self.remove(&key);
+            is_changed = true;
+        }
+
+        if let (Some(modified), true) = (modified, is_readable) {
+            self.dirs_modified.insert(dir_key.to_string(), modified);
+            is_changed = true;
+        }
+
+        Ok(is_changed)
+    }
+
+    /// Returns every indexed entry, sorted from oldest to newest
+    pub(crate) fn get_entries(&self) -> Result<Vec<core::file::EntryFile>, core::file::FileError> {
+        let data_dir = core::file::get_data_dir_path()?;
+
+        let mut entries: Vec<core::file::EntryFile> = self
+            .entries
+            .iter()
+            .map(|(key, indexed)| core::file::EntryFile {
+                datetime: indexed.datetime,
+                path: data_dir.join(key),
+            })
+            .collect();
+
+        entries.sort_by_key(|entry| entry.datetime);
+
+        Ok(entries)
+    }
+
+    /// Returns the keys of entries that may contain `query` as plain text.
+    ///
+    /// Every word of `query` must appear in some indexed word of the entry.
+    /// Matching is case-insensitive, so the result may contain entries that a
+    /// case-sensitive search would then reject.
+    pub(crate) fn get_candidates_for_text(&self, query: &str) -> BTreeSet<String> {
+        let mut candidates: Option<BTreeSet<String>> = None;
+
+        for query_term in get_terms(query) {
+            let keys: BTreeSet<String> = self
+                .terms
+                .iter()
+                .filter(|(term, _)| term.contains(&query_term))
+                .flat_map(|(_, keys)| keys.iter().cloned())
+                .collect();
+
+            candidates = Some(match candidates {
+                Some(candidates) => candidates.intersection(&keys).cloned().collect(),
+                None => keys,
+            });
+        }
+
+        candidates.unwrap_or_else(|| self.entries.keys().cloned().collect())
+    }
+}
+
+/// Returns the key of an entry in the index, i.e. its path relative to the data directory
+pub(crate) fn get_index_key(path: &std::path::Path) -> String {
+    let relative_path = core::file::get_data_dir_path()
+        .ok()
+        .and_then(|data_dir| path.strip_prefix(data_dir).ok().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| path.to_path_buf());
+
+    relative_path.to_string_lossy().replace('\\', "/")
+}
+
+fn get_modified_time(path: &std::path::Path) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Returns the key of the `YYYY/MM` directory that an entry, given its key, is in
+fn get_month_dir_key(key: &str) -> &str {
+    key.rsplit_once('/').map_or("", |(dir_key, _)| dir_key)
+}
+
+/// Returns when each `YYYY/MM` directory of the data directory was last modified, keyed by its
+/// path relative to the data directory.  Hidden directories, e.g. the trash, are skipped.
+fn get_month_dirs(data_dir: &std::path::Path) -> BTreeMap<String, Option<std::time::SystemTime>> {
+    let get_subdirs = |dir: &std::path::Path| -> Vec<(String, std::path::PathBuf)> {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+            .filter(|(name, _)| !name.starts_with('.'))
+            .collect()
+    };
+
+    let mut month_dirs = BTreeMap::new();
+    for (year, year_dir) in get_subdirs(data_dir) {
+        for (month, month_dir) in get_subdirs(&year_dir) {
+            month_dirs.insert(format!("{}/{}", year, month), get_modified_time(&month_dir));
+        }
+    }
+
+    month_dirs
+}
+
+/// Splits text into the lowercased words used by the inverted index
+fn get_terms(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Builds the index from scratch by reading every entry in the data directory
+pub(crate) fn build_index() -> Result<JournalIndex, IndexError> {
+    let mut index = JournalIndex {
+        version: INDEX_VERSION,
+        ..Default::default()
+    };
+
+    // Looked at first, so that directories that change while their entries are read are read
+    // again next time
+    let month_dirs = get_month_dirs(&core::file::get_data_dir_path()?);
+
+    let mut unreadable_dir_keys: BTreeSet<String> = BTreeSet::new();
+    for entry in core::file::get_all_entries()? {
+        if !index.insert_from_file(&entry) {
+            unreadable_dir_keys.insert(get_month_dir_key(&get_index_key(&entry.path)).to_string());
+        }
+    }
+
+    for (dir_key, modified) in month_dirs {
+        if let (Some(modified), false) = (modified, unreadable_dir_keys.contains(&dir_key)) {
+            index.dirs_modified.insert(dir_key, modified);
+        }
+    }
+
+    Ok(index)
+}
+
+/// Reads the index from the data directory.
+///
+/// If there is no index yet, or it was written by an incompatible version,
+/// it is rebuilt from the entries and saved.  Otherwise, only the `YYYY/MM`
+/// directories that changed on disk since they were indexed are read again.  An encrypted
+/// journal is unlocked first, as the index holds what was written too.
+pub(crate) fn load_index() -> Result<JournalIndex, IndexError> {
+    let index_path = core::file::get_index_file_path()?;
+
+    let bytes = match core::file::read_journal_file(&index_path) {
+        Ok(bytes) => Some(bytes),
+        // A damaged index is rebuilt like a missing one, but a locked journal cannot be
+        Err(core::file::FileError::Encryption(error))
+            if !matches!(error, core::crypto::CryptoError::Damaged) =>
+        {
+            return Err(core::file::FileError::Encryption(error).into());
+        }
+        Err(_) => None,
+    };
+    let existing_index = bytes
+        .and_then(|bytes| serde_json::from_slice::<JournalIndex>(&bytes).ok())
+        .filter(|index| index.version == INDEX_VERSION);
+
+    match existing_index {
+        Some(mut index) => {
+            if index.refresh()? {
+                save_index(&index)?;
+            }
+            Ok(index)
+        }
+        None => {
+            let index = build_index()?;
+            save_index(&index)?;
+            Ok(index)
+        }
+    }
+}
+
+pub(crate) fn save_index(index: &JournalIndex) -> Result<(), IndexError> {
+    core::file::write_journal_file(
+        &core::file::get_index_file_path()?,
+        &serde_json::to_vec(index)?,
+    )?;
+
+    Ok(())
+}
+
+/// Updates the index after an entry has been written
+pub(crate) fn record_entry(
+    entry: &core::file::EntryFile,
+    contents: &str,
+) -> Result<(), IndexError> {
+    let mut index = load_index()?;
+    index.insert(entry, contents);
+    save_index(&index)
+}
+
+/// Updates the index after an entry has been removed from the journal
+pub(crate) fn forget_entry(path: &std::path::Path) -> Result<(), IndexError> {
+    let mut index = load_index()?;
+    index.remove(&get_index_key(path));
+    save_index(&index)
+}
@@ -8,10 +8,26 @@
 //!
 //! The module handles API calls, file path-finding, writing entries.
 
+#[cfg(unix)]
+pub(crate) mod agent;
+pub(crate) mod calendar;
 pub(crate) mod chrono;
+pub(crate) mod crypto;
+pub(crate) mod entry;
 pub(crate) mod file;
 pub(crate) mod geo;
+pub(crate) mod habits;
+pub(crate) mod history;
+pub(crate) mod index;
+pub(crate) mod people;
+pub(crate) mod review;
+pub(crate) mod search;
+pub(crate) mod stats;
 pub(crate) mod str_man;
+pub(crate) mod tags;
+pub(crate) mod template;
+pub(crate) mod tracking;
+pub(crate) mod trash;
 pub(crate) mod weather;
 pub(crate) mod helper;
 
@@ -70,6 +86,39 @@ impl std::fmt::Display for Location {
     }
 }
 
+/// Struct for a location that entries can be written from
+///
+/// Unlike `Location`, this holds only what the journal needs, and is what gets
+/// stored in Journey's own files.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Place {
+    pub(crate) full_name: String,
+    pub(crate) latitude: f64,
+    pub(crate) longitude: f64,
+    pub(crate) timezone: String,
+}
+
+impl Place {
+    pub(crate) fn from_location(full_name: String, location: &Location) -> Self {
+        Place {
+            full_name,
+            latitude: location.latitude,
+            longitude: location.longitude,
+            timezone: location.timezone.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Place {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, {}) with timezone '{}'",
+            self.full_name, self.latitude, self.longitude, self.timezone
+        )
+    }
+}
+
 /// Struct for Open Meteo's geolocation API
 ///
 /// # Note
@@ -0,0 +1,198 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains functions for the people mentioned in entries
+//!
+//! People are mentioned with `@name` in the body of an entry.  Names are
+//! compared in lowercase, and a person can go by several names through the
+//! aliases in the `[people]` table of the config file, e.g. `mum = "mom"`.
+
+use std::collections::BTreeMap;
+
+use crate::core;
+
+/// Returns the name as it is compared, e.g. `alice` for `@Alice`
+pub(crate) fn normalise_name(name: &str) -> String {
+    name.trim().trim_start_matches('@').to_lowercase()
+}
+
+/// Returns the person that `name` refers to, following the aliases in the config file
+pub(crate) fn resolve_name(name: &str, aliases: &BTreeMap<String, String>) -> String {
+    let name = normalise_name(name);
+
+    aliases
+        .iter()
+        .find(|(alias, _)| normalise_name(alias) == name)
+        .map_or(name, |(_, person)| normalise_name(person))
+}
+
+/// Returns every `@name` in `text`, lowercased and without the `@`, with how often it appears.
+/// A possessive `'s` is not part of the name, i.e. `@alice's` is a mention of `alice`.
+pub(crate) fn extract_mentions(text: &str) -> BTreeMap<String, usize> {
+    let mut mentions: BTreeMap<String, usize> = BTreeMap::new();
+
+    for name in text
+        .split_whitespace()
+        .filter_map(|word| core::str_man::get_marked_word(word, '@'))
+    {
+        let name = name.to_lowercase();
+        let name = ["'s", "\u{2019}s"]
+            .into_iter()
+            .find_map(|possessive| name.strip_suffix(possessive))
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&name);
+
+        *mentions.entry(name.to_string()).or_default() += 1;
+    }
+
+    mentions
+}
+
+/// Returns the lines of `text` that mention `person` by any of their names
+pub(crate) fn find_mentioning_lines<'a>(
+    text: &'a str,
+    person: &str,
+    aliases: &BTreeMap<String, String>,
+) -> Vec<&'a str> {
+    text.lines()
+        .filter(|line| {
+            extract_mentions(line)
+                .keys()
+                .any(|name| resolve_name(name, aliases) == person)
+        })
+        .collect()
+}
+
+/// struct PersonUsage
+/// How often, and when last, a person was mentioned
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PersonUsage {
+    pub(crate) name: String,
+    pub(crate) mention_count: usize,
+    pub(crate) entry_count: usize,
+    pub(crate) last_seen: chrono::NaiveDateTime,
+}
+
+/// Returns everyone mentioned in the index, most mentioned first
+pub(crate) fn get_people(
+    index: &core::index::JournalIndex,
+    aliases: &BTreeMap<String, String>,
+) -> Vec<PersonUsage> {
+    let mut people: BTreeMap<String, PersonUsage> = BTreeMap::new();
+
+    for entry in index.entries.values() {
+        // Counted per person, as an entry may mention someone by several names
+        let mut entry_mentions: BTreeMap<String, usize> = BTreeMap::new();
+        for (name, count) in entry.mentions.iter() {
+            *entry_mentions
+                .entry(resolve_name(name, aliases))
+                .or_default() += count;
+        }
+
+        for (name, count) in entry_mentions {
+            let person = people.entry(name.clone()).or_insert_with(|| PersonUsage {
+                name,
+                mention_count: 0,
+                entry_count: 0,
+                last_seen: entry.datetime,
+            });
+            person.mention_count += count;
+            person.entry_count += 1;
+            person.last_seen = person.last_seen.max(entry.datetime);
+        }
+    }
+
+    let mut people: Vec<PersonUsage> = people.into_values().collect();
+    people.sort_by_key(|person| std::cmp::Reverse(person.mention_count));
+
+    people
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("mum".to_string(), "mom".to_string()),
+            ("Ally".to_string(), "@Alice".to_string()),
+        ])
+    }
+
+    #[test]
+    fn extracts_mentions_without_punctuation_or_possessives() {
+        assert_eq!(
+            extract_mentions(
+                "Lunch with @Alice, @alice's sister and @mum\u{2019}s friend.  @ alone"
+            ),
+            BTreeMap::from([("alice".to_string(), 2), ("mum".to_string(), 1)])
+        );
+        assert_eq!(
+            extract_mentions("@@alice email@alice @'s"),
+            BTreeMap::from([("'s".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn resolves_names_through_aliases() {
+        assert_eq!(resolve_name("@Mum", &aliases()), "mom");
+        assert_eq!(resolve_name("ally", &aliases()), "alice");
+        assert_eq!(resolve_name("Bob", &aliases()), "bob");
+    }
+
+    #[test]
+    fn counts_people_across_entries_and_aliases() {
+        let mut index = core::index::JournalIndex::default();
+        for (day, body) in [
+            (1, "Called @mum.  @Mom says hi."),
+            (2, "Dinner at @mum's with @alice."),
+            (3, "Nobody today."),
+        ] {
+            let datetime = chrono::NaiveDate::from_ymd_opt(2024, 6, day)
+                .unwrap()
+                .and_hms_opt(8, 0, 0)
+                .unwrap();
+            let entry = core::file::EntryFile {
+                datetime,
+                path: std::path::PathBuf::from(format!("2024/06/{:02}.08-00.txt", day)),
+            };
+            index.insert(
+                &entry,
+                &format!(
+                    "DATE: {} PST (+08:00)\n\n{}\n",
+                    datetime.format(core::entry::DATE_FORMAT),
+                    body
+                ),
+            );
+        }
+
+        let people = get_people(&index, &aliases());
+        let last_seen = |day| {
+            chrono::NaiveDate::from_ymd_opt(2024, 6, day)
+                .unwrap()
+                .and_hms_opt(8, 0, 0)
+                .unwrap()
+        };
+
+        assert_eq!(
+            people,
+            vec![
+                PersonUsage {
+                    name: "mom".to_string(),
+                    mention_count: 3,
+                    entry_count: 2,
+                    last_seen: last_seen(2),
+                },
+                PersonUsage {
+                    name: "alice".to_string(),
+                    mention_count: 1,
+                    entry_count: 1,
+                    last_seen: last_seen(2),
+                },
+            ]
+        );
+    }
+}
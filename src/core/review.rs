@@ -0,0 +1,169 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the drafts of the weekly, monthly and yearly reviews that `review` writes
+//!
+//! Reviews are kept apart from entries, in the `reviews` directory of the data
+//! directory, as `2024-W26.txt`, `2024-06.txt` or `2024.txt`.
+
+use std::collections::BTreeMap;
+
+use crate::core;
+use crate::core::tracking::{Metric, Period};
+
+/// The questions the draft ends with, each followed by room to answer
+const QUESTIONS: [&str; 3] = [
+    "What went well?",
+    "What could have gone better?",
+    "What will I focus on next?",
+];
+
+/// Generates the draft of the review of the period `date` falls in.
+///
+/// # Arguments
+///
+/// * `entries` - The period's entries, as index keys and indexed entries, oldest first.
+///   The keys are used to link to each entry from the review.
+pub(crate) fn generate_review(
+    period: Period,
+    date: chrono::NaiveDate,
+    entries: &[(&str, &core::index::IndexedEntry)],
+) -> String {
+    let (first_day, last_day) = period.get_range(date);
+    let indexed_entries: Vec<&core::index::IndexedEntry> =
+        entries.iter().map(|(_, entry)| *entry).collect();
+
+    let mut review = format!(
+        "REVIEW: {}\nPERIOD: {} to {}\n\n",
+        period.get_name(date),
+        first_day.format("%a, %Y %b %d"),
+        last_day.format("%a, %Y %b %d"),
+    );
+
+    if entries.is_empty() {
+        review.push_str("There are no entries in this period.\n");
+    } else {
+        review.push_str(&generate_summary(
+            &indexed_entries,
+            period,
+            (last_day - first_day).num_days() + 1,
+            last_day,
+        ));
+
+        review.push_str("\nENTRIES\n");
+        for (key, entry) in entries.iter() {
+            review.push_str(&format!(
+                "{}  {}\n    ../{}\n",
+                entry.datetime.format("%a %d %b %H:%M"),
+                entry.summary.as_deref().unwrap_or(""),
+                key
+            ));
+        }
+    }
+
+    for question in QUESTIONS {
+        review.push_str(&format!("\n{}\n\n", question));
+    }
+
+    review
+}
+
+/// Summarises the entries: how much was written, tags, averages and notable weather
+fn generate_summary(
+    entries: &[&core::index::IndexedEntry],
+    period: Period,
+    day_count: i64,
+    last_day: chrono::NaiveDate,
+) -> String {
+    let stats = core::stats::get_stats(entries, last_day);
+    let days_written = entries
+        .iter()
+        .map(|entry| entry.datetime.date())
+        .collect::<std::collections::BTreeSet<chrono::NaiveDate>>()
+        .len();
+
+    let mut summary = format!(
+        "Entries: {} on {} of {} days\nWords: {} ({:.0} per entry)\nLongest streak: {} {}\n",
+        stats.totals.entry_count,
+        days_written,
+        day_count,
+        stats.totals.word_count,
+        stats.totals.average_word_count,
+        stats.longest_streak,
+        if stats.longest_streak == 1 {
+            "day"
+        } else {
+            "days"
+        },
+    );
+
+    let mut tags: BTreeMap<&str, usize> = BTreeMap::new();
+    for tag in entries.iter().flat_map(|entry| entry.tags.iter()) {
+        *tags.entry(tag).or_default() += 1;
+    }
+    if !tags.is_empty() {
+        let mut tags: Vec<(&str, usize)> = tags.into_iter().collect();
+        tags.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|(tag, count)| format!("#{} ({})", tag, count))
+            .collect();
+        summary.push_str(&format!("Tags: {}\n", tags.join(", ")));
+    }
+
+    // The whole review is a single period, so there is at most one
+    if let Some(period_averages) = core::tracking::get_period_averages(entries, period).first() {
+        let averages: Vec<String> = Metric::ALL
+            .iter()
+            .zip(period_averages.averages.iter())
+            .filter_map(|(metric, average)| {
+                Some(format!(
+                    "{} {:.1}",
+                    metric.get_field().to_lowercase(),
+                    (*average)?
+                ))
+            })
+            .collect();
+        summary.push_str(&format!("Averages: {}\n", averages.join(", ")));
+    }
+
+    let with_weather: Vec<(&core::index::IndexedEntry, &core::weather::Weather)> = entries
+        .iter()
+        .filter_map(|entry| Some((*entry, entry.weather.as_ref()?)))
+        .collect();
+    let describe = |entry: &core::index::IndexedEntry| {
+        format!(
+            "{}{}",
+            entry.datetime.format("%a %d %b"),
+            entry
+                .location
+                .as_deref()
+                .map_or(String::new(), |location| format!(" in {}", location))
+        )
+    };
+    if let Some((entry, weather)) = with_weather
+        .iter()
+        .max_by(|(_, a), (_, b)| a.temperature.total_cmp(&b.temperature))
+    {
+        summary.push_str(&format!(
+            "Hottest: {} C, {}\n",
+            weather.temperature,
+            describe(entry)
+        ));
+    }
+    if let Some((entry, weather)) = with_weather
+        .iter()
+        .filter(|(_, weather)| weather.rain > 0.0)
+        .max_by(|(_, a), (_, b)| a.rain.total_cmp(&b.rain))
+    {
+        summary.push_str(&format!(
+            "Wettest: {} mm of rain, {}\n",
+            weather.rain,
+            describe(entry)
+        ));
+    }
+
+    summary
+}
@@ -0,0 +1,83 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains functions for searching through the contents of entries
+
+/// A line of an entry that is either a match or context around a match
+#[derive(Debug)]
+pub(crate) struct SearchLine<'a> {
+    /// The line's number in the entry, counting from 1
+    pub(crate) line_number: usize,
+    pub(crate) text: &'a str,
+    pub(crate) is_match: bool,
+}
+
+/// Builds the regex used for searching.
+///
+/// # Arguments
+///
+/// * `query` - The text or pattern to look for.
+/// * `is_regex` - If `false`, `query` is matched literally.
+/// * `ignore_case` - If `true`, matching is case-insensitive.
+pub(crate) fn build_query_regex(
+    query: &str,
+    is_regex: bool,
+    ignore_case: bool,
+) -> Result<regex::Regex, regex::Error> {
+    let pattern = if is_regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+
+    regex::RegexBuilder::new(&pattern)
+        .case_insensitive(ignore_case)
+        .build()
+}
+
+/// Finds the lines of `contents` that match `query`.
+///
+/// # Returns
+///
+/// Groups of consecutive lines, each made of matching lines and up to `context`
+/// lines before and after them.  Groups whose context would overlap are merged.
+pub(crate) fn find_matching_lines<'a>(
+    contents: &'a str,
+    query: &regex::Regex,
+    context: usize,
+) -> Vec<Vec<SearchLine<'a>>> {
+    let lines: Vec<&str> = contents.lines().collect();
+
+    let matching_indices: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| query.is_match(line))
+        .map(|(i, _)| i)
+        .collect();
+
+    // Merge the ranges of lines around each match
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for i in matching_indices.iter() {
+        let start = i.saturating_sub(context);
+        let end = (i + context).min(lines.len() - 1);
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            (start..=end)
+                .map(|i| SearchLine {
+                    line_number: i + 1,
+                    text: lines[i],
+                    is_match: matching_indices.binary_search(&i).is_ok(),
+                })
+                .collect()
+        })
+        .collect()
+}
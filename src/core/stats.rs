@@ -0,0 +1,137 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the writing statistics that `stats` reports
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Timelike};
+
+use crate::core;
+
+/// struct Totals
+/// How much was written, over the whole journal or a year of it
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize)]
+pub(crate) struct Totals {
+    pub(crate) entry_count: usize,
+    pub(crate) word_count: usize,
+    pub(crate) average_word_count: f64,
+}
+
+impl Totals {
+    fn from_entries<'a>(entries: impl Iterator<Item = &'a core::index::IndexedEntry>) -> Self {
+        let (entry_count, word_count) = entries.fold((0, 0), |(entry_count, word_count), entry| {
+            (entry_count + 1, word_count + entry.word_count)
+        });
+
+        Totals {
+            entry_count,
+            word_count,
+            average_word_count: if entry_count > 0 {
+                word_count as f64 / entry_count as f64
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// struct YearStats
+/// The totals of a single year
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub(crate) struct YearStats {
+    pub(crate) year: i32,
+    #[serde(flatten)]
+    pub(crate) totals: Totals,
+}
+
+/// struct LocationCount
+/// How many entries were written from a location
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub(crate) struct LocationCount {
+    pub(crate) location: String,
+    pub(crate) entry_count: usize,
+}
+
+/// struct JournalStats
+/// Everything `stats` reports
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub(crate) struct JournalStats {
+    #[serde(flatten)]
+    pub(crate) totals: Totals,
+    /// In days with at least one entry
+    pub(crate) current_streak: usize,
+    pub(crate) longest_streak: usize,
+    /// The weekday and hour of day with the most entries, if there are any entries
+    pub(crate) busiest_weekday: Option<chrono::Weekday>,
+    pub(crate) busiest_hour: Option<u32>,
+    /// Most entries first
+    pub(crate) locations: Vec<LocationCount>,
+    /// Oldest first
+    pub(crate) years: Vec<YearStats>,
+}
+
+/// Returns the key with the highest count, the smallest key on a tie
+fn get_most_common<K: Ord + Copy>(counts: &BTreeMap<K, usize>) -> Option<K> {
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| **count)
+        .map(|(key, _)| *key)
+}
+
+/// Returns the statistics of `entries`, with the current streak counted back from `today`
+pub(crate) fn get_stats(
+    entries: &[&core::index::IndexedEntry],
+    today: chrono::NaiveDate,
+) -> JournalStats {
+    let days = entries.iter().map(|entry| entry.datetime.date()).collect();
+    let (current_streak, longest_streak) = core::chrono::get_streaks(&days, today);
+
+    let mut weekdays: BTreeMap<u32, usize> = BTreeMap::new();
+    let mut hours: BTreeMap<u32, usize> = BTreeMap::new();
+    let mut locations: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut years: BTreeMap<i32, Vec<&core::index::IndexedEntry>> = BTreeMap::new();
+    for entry in entries.iter() {
+        *weekdays
+            .entry(entry.datetime.weekday().num_days_from_monday())
+            .or_default() += 1;
+        *hours.entry(entry.datetime.hour()).or_default() += 1;
+        if let Some(location) = entry.location.as_deref() {
+            *locations.entry(location).or_default() += 1;
+        }
+        years.entry(entry.datetime.year()).or_default().push(entry);
+    }
+
+    let mut locations: Vec<LocationCount> = locations
+        .into_iter()
+        .map(|(location, entry_count)| LocationCount {
+            location: location.to_string(),
+            entry_count,
+        })
+        .collect();
+    locations.sort_by_key(|location| std::cmp::Reverse(location.entry_count));
+
+    JournalStats {
+        totals: Totals::from_entries(entries.iter().copied()),
+        current_streak,
+        longest_streak,
+        busiest_weekday: get_most_common(&weekdays)
+            .and_then(|weekday| chrono::Weekday::try_from(weekday as u8).ok()),
+        busiest_hour: get_most_common(&hours),
+        locations,
+        years: years
+            .into_iter()
+            .map(|(year, entries)| YearStats {
+                year,
+                totals: Totals::from_entries(entries.into_iter()),
+            })
+            .collect(),
+    }
+}
@@ -18,3 +18,14 @@ pub(crate) fn split_location(raw_location: &str) -> &str {
 pub(crate) fn sanitise_spaces_html(input: &str) -> String {
     input.replace(" ", "%20")
 }
+
+/// Returns what follows `marker` in a word like `#work` or `@alice`, without any
+/// trailing punctuation, or `None` if the word does not start with `marker`.
+pub(crate) fn get_marked_word(word: &str, marker: char) -> Option<&str> {
+    let marked_word = word
+        .strip_prefix(marker)?
+        .trim_end_matches(|c: char| !c.is_alphanumeric());
+
+    Some(marked_word)
+        .filter(|marked_word| !marked_word.is_empty() && !marked_word.starts_with(marker))
+}
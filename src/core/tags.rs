@@ -0,0 +1,315 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains functions for the tags of entries
+//!
+//! An entry's tags are the `#words` in its body and the comma- or
+//! space-separated words of an optional `TAGS:` line in its header.  Tags are
+//! compared in lowercase and without the `#`.
+
+use std::collections::BTreeSet;
+
+use crate::core;
+
+/// Header field listing tags besides the ones in the body
+pub(crate) const TAGS_FIELD: &str = "TAGS";
+
+/// Returns the tag as it is compared and stored, e.g. `work` for `#Work`
+pub(crate) fn normalise_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// Returns the part of a `#word` that is the tag, i.e. without the `#` and any trailing punctuation
+fn get_tag_of_word(word: &str) -> Option<&str> {
+    core::str_man::get_marked_word(word, '#')
+}
+
+/// Returns whether `tag`, as returned by `normalise_tag`, would be read back as the same tag
+/// both as a `#word` and in a `TAGS:` line, e.g. not `work.` or `work life`
+pub(crate) fn is_valid_tag(tag: &str) -> bool {
+    !tag.contains(|c: char| c.is_whitespace() || c == ',')
+        && get_tag_of_word(&format!("#{}", tag)) == Some(tag)
+}
+
+/// Returns every `#tag` in `text`, lowercased and without the `#`
+pub(crate) fn extract_tags(text: &str) -> BTreeSet<String> {
+    text.split_whitespace()
+        .filter_map(get_tag_of_word)
+        .map(|tag| tag.to_lowercase())
+        .collect()
+}
+
+/// Returns the tags listed in the value of a `TAGS:` line, e.g. `work, #travel`
+fn parse_tags_field(value: &str) -> BTreeSet<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(normalise_tag)
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Returns all the tags of an entry, from both its body and its `TAGS:` line
+pub(crate) fn get_tags(contents: &str) -> BTreeSet<String> {
+    let mut tags = extract_tags(core::entry::get_body(contents));
+
+    if let Some(value) = core::entry::get_header_field(contents, TAGS_FIELD) {
+        tags.extend(parse_tags_field(&value));
+    }
+
+    tags
+}
+
+/// Renames every `#old` in `text` to `#new`, keeping any punctuation after the tag
+fn rename_tag_in_text(text: &str, old: &str, new: &str) -> String {
+    text.split_inclusive(char::is_whitespace)
+        .map(|piece| {
+            let word = piece.trim_end_matches(char::is_whitespace);
+
+            match get_tag_of_word(word) {
+                Some(tag) if tag.to_lowercase() == old => {
+                    // `tag` starts right after the `#`
+                    format!("#{}{}", new, &piece[1 + tag.len()..])
+                }
+                _ => piece.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Renames `old` to `new` in the value of a `TAGS:` line, dropping the duplicate
+/// if the entry already had `new`.  The value may be quoted, as in front matter.
+fn rename_tag_in_field(value: &str, old: &str, new: &str) -> String {
+    let quote = if value.starts_with('"') && value.ends_with('"') && value.len() > 1 {
+        "\""
+    } else {
+        ""
+    };
+    let list = &value[quote.len()..value.len() - quote.len()];
+
+    let mut tags: Vec<String> = vec![];
+    for tag in list
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|tag| !tag.is_empty())
+    {
+        let tag = if normalise_tag(tag) == old {
+            // Keep the `#` if the user wrote one
+            format!(
+                "{}{}",
+                &tag[..tag.len() - tag.trim_start_matches('#').len()],
+                new
+            )
+        } else {
+            tag.to_string()
+        };
+        if !tags
+            .iter()
+            .any(|known| normalise_tag(known) == normalise_tag(&tag))
+        {
+            tags.push(tag);
+        }
+    }
+
+    format!("{}{}{}", quote, tags.join(", "), quote)
+}
+
+/// Renames the tag `old` to `new` everywhere in an entry: in the body, in the
+/// `TAGS:` line, and in the front matter.  If the entry already has `new`,
+/// the two tags are merged.
+///
+/// Returns `None` if the entry does not have the tag.
+pub(crate) fn rename_tag(contents: &str, old: &str, new: &str) -> Option<String> {
+    let (old, new) = (normalise_tag(old), normalise_tag(new));
+
+    if !get_tags(contents).contains(&old) {
+        return None;
+    }
+
+    let mut renamed = String::with_capacity(contents.len());
+    let mut lines = contents.split_inclusive('\n').peekable();
+
+    // Front matter, where the field is written as `TAGS = "..."`
+    if lines.peek().is_some_and(|line| line.trim_end() == "+++") {
+        renamed.push_str(lines.next().unwrap_or_default());
+        for line in lines.by_ref() {
+            let rewritten = match line.split_once('=') {
+                Some((key, value)) if key.trim().eq_ignore_ascii_case(TAGS_FIELD) => {
+                    let line_ending = &value[value.trim_end().len()..];
+                    format!(
+                        "{}= {}{}",
+                        key,
+                        rename_tag_in_field(value.trim(), &old, &new),
+                        line_ending
+                    )
+                }
+                _ => line.to_string(),
+            };
+            renamed.push_str(&rewritten);
+            if line.trim_end() == "+++" {
+                break;
+            }
+        }
+    }
+
+    // The header, up to the first blank line
+    for line in lines.by_ref() {
+        let rewritten = match line.split_once(':') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case(TAGS_FIELD) => {
+                let line_ending = &value[value.trim_end().len()..];
+                format!(
+                    "{}: {}{}",
+                    key,
+                    rename_tag_in_field(value.trim(), &old, &new),
+                    line_ending
+                )
+            }
+            // In case the entry has no header and this is already the body
+            _ => rename_tag_in_text(line, &old, &new),
+        };
+        renamed.push_str(&rewritten);
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+
+    // The rest, where tags are `#words`
+    let rest: String = lines.collect();
+    renamed.push_str(&rename_tag_in_text(&rest, &old, &new));
+
+    Some(renamed)
+}
+
+/// struct TagUsage
+/// How often, and when, a tag was used
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TagUsage {
+    pub(crate) tag: String,
+    pub(crate) entry_count: usize,
+    pub(crate) first_used: chrono::NaiveDateTime,
+    pub(crate) last_used: chrono::NaiveDateTime,
+}
+
+/// Returns the usage of every tag in the index, most used first
+pub(crate) fn get_tag_usages(index: &core::index::JournalIndex) -> Vec<TagUsage> {
+    let mut usages: std::collections::BTreeMap<&str, TagUsage> = std::collections::BTreeMap::new();
+
+    for entry in index.entries.values() {
+        for tag in entry.tags.iter() {
+            let usage = usages.entry(tag).or_insert_with(|| TagUsage {
+                tag: tag.clone(),
+                entry_count: 0,
+                first_used: entry.datetime,
+                last_used: entry.datetime,
+            });
+            usage.entry_count += 1;
+            usage.first_used = usage.first_used.min(entry.datetime);
+            usage.last_used = usage.last_used.max(entry.datetime);
+        }
+    }
+
+    let mut usages: Vec<TagUsage> = usages.into_values().collect();
+    usages.sort_by_key(|usage| std::cmp::Reverse(usage.entry_count));
+
+    usages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_tags_in_the_body_keeping_punctuation() {
+        let contents = "\
+DATE: Mon, 2024 Jun 24 14:03:05 PST (+08:00)
+
+Finished the #wrk report.  More #WRK tomorrow, then #wrk!
+Not tags: (#wrk) #wrkshop #wrk's email#wrk
+";
+
+        assert_eq!(
+            rename_tag(contents, "wrk", "work").unwrap(),
+            "\
+DATE: Mon, 2024 Jun 24 14:03:05 PST (+08:00)
+
+Finished the #work report.  More #work tomorrow, then #work!
+Not tags: (#wrk) #wrkshop #wrk's email#wrk
+"
+        );
+    }
+
+    #[test]
+    fn renames_tags_in_the_header_merging_duplicates() {
+        let contents = "\
+DATE: Mon, 2024 Jun 24 14:03:05 PST (+08:00)
+TAGS: #wrk, travel work
+
+Went to the office.
+";
+
+        assert_eq!(
+            rename_tag(contents, "#WRK", "work").unwrap(),
+            "\
+DATE: Mon, 2024 Jun 24 14:03:05 PST (+08:00)
+TAGS: #work, travel
+
+Went to the office.
+"
+        );
+    }
+
+    #[test]
+    fn renames_tags_in_the_front_matter() {
+        let contents = "\
++++
+schema = 1
+date = \"2024-06-24T14:03:05+08:00\"
+timezone_abbreviation = \"PST\"
+
+[fields]
+TAGS = \"wrk, travel\"
++++
+DATE: Mon, 2024 Jun 24 14:03:05 PST (+08:00)
+TAGS: wrk, travel
+
+Went to the office. #wrk
+";
+
+        assert_eq!(
+            rename_tag(contents, "wrk", "work").unwrap(),
+            "\
++++
+schema = 1
+date = \"2024-06-24T14:03:05+08:00\"
+timezone_abbreviation = \"PST\"
+
+[fields]
+TAGS = \"work, travel\"
++++
+DATE: Mon, 2024 Jun 24 14:03:05 PST (+08:00)
+TAGS: work, travel
+
+Went to the office. #work
+"
+        );
+    }
+
+    #[test]
+    fn leaves_entries_without_the_tag_alone() {
+        let contents = "DATE: Mon, 2024 Jun 24 14:03:05 PST (+08:00)\n\n#wrkshop #travel\n";
+
+        assert_eq!(rename_tag(contents, "wrk", "work"), None);
+    }
+
+    #[test]
+    fn tags_must_read_back_as_written() {
+        assert!(is_valid_tag("work"));
+        assert!(is_valid_tag("work-life"));
+
+        assert!(!is_valid_tag(""));
+        assert!(!is_valid_tag("work."));
+        assert!(!is_valid_tag("work life"));
+        assert!(!is_valid_tag("work,life"));
+    }
+}
@@ -0,0 +1,585 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the preamble templates that can replace the built-in preamble of new entries,
+//! and the writing prompts that can be added to the body of new entries
+//!
+//! A template is plain text with placeholders in braces, e.g. `{temperature}`.
+//!
+//! * `{date}` is the date as in the built-in `DATE:` line.  `{date:FORMAT}`
+//!   formats it with a `strftime`-style format, where `%Z` is the time zone's
+//!   abbreviation.
+//! * `{if NAME}...{end}` only keeps its contents if `NAME` has a value that is
+//!   not zero, e.g. `{if rain}`.  `{if not NAME}`, `{else}`, and the conditions
+//!   `day` and `night` are also available.
+//! * `{{` and `}}` are a literal `{` and `}`.
+//!
+//! Values that are not available, e.g. the UV index of past dates, are written as `n/a`.
+
+use crate::core;
+
+/// Placeholders that can be used in a template, with a short description of each
+pub(crate) const PLACEHOLDERS: [(&str, &str); 22] = [
+    (
+        "date",
+        "the date, e.g. 'Mon, 2024 Jun 24 14:03:05 PST (+08:00)'",
+    ),
+    ("timezone", "the time zone's abbreviation, e.g. 'PST'"),
+    ("offset", "the time zone's offset, e.g. '+08:00'"),
+    ("location", "the full location, e.g. 'Intramuros, Manila'"),
+    (
+        "location_name",
+        "the last part of the location, e.g. 'Manila'",
+    ),
+    (
+        "location_details",
+        "the location without its last part, e.g. 'Intramuros'",
+    ),
+    ("temperature", "in °C"),
+    ("apparent_temperature", "in °C"),
+    ("weather_code", "the WMO weather code"),
+    ("weather_description", "e.g. 'Partly cloudy skies'"),
+    ("rain", "in mm"),
+    ("windspeed", "in km/h"),
+    ("winddirection", "in degrees"),
+    ("wind_direction", "as a compass direction, e.g. 'SSW'"),
+    ("pressure", "in hPa"),
+    ("humidity", "in %"),
+    ("visibility", "in m"),
+    ("visibility_km", "in km"),
+    ("uv_index", "the day's highest UV index"),
+    ("sunrise", "e.g. '05:28'"),
+    ("sunset", "e.g. '18:29'"),
+    ("template", "the entry template given to `new --template`"),
+];
+
+/// Conditions that can be used in `{if ...}` besides the placeholders
+const CONDITIONS: [&str; 2] = ["day", "night"];
+
+/// Written in place of values that are not available
+const NOT_AVAILABLE: &str = "n/a";
+
+/// enum TemplateErrorKind
+/// What is wrong with a template
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TemplateErrorKind {
+    UnknownPlaceholder(String),
+    UnknownCondition(String),
+    UnexpectedFormat(String),
+    InvalidDateFormat(String),
+    UnclosedBrace,
+    UnmatchedClosingBrace,
+    UnexpectedElse,
+    UnexpectedEnd,
+    UnclosedIf(String),
+}
+
+/// struct TemplateError
+/// An error in a template, with the line and column (counting from 1) where it happened
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TemplateError {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) kind: TemplateErrorKind,
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            TemplateErrorKind::UnknownPlaceholder(ref name) => {
+                write!(f, "unknown placeholder `{{{}}}`", name)?;
+                match get_similar_placeholder(name) {
+                    Some(similar) => write!(f, ".  Did you mean `{{{}}}`?", similar),
+                    None => Ok(()),
+                }
+            }
+            TemplateErrorKind::UnknownCondition(ref name) => write!(
+                f,
+                "unknown condition `{}`.  Conditions can be a placeholder, `day`, or `night`",
+                name
+            ),
+            TemplateErrorKind::UnexpectedFormat(ref name) => {
+                write!(f, "only `{{date}}` takes a format, not `{{{}}}`", name)
+            }
+            TemplateErrorKind::InvalidDateFormat(ref format) => {
+                write!(f, "'{}' is not a valid date format", format)
+            }
+            TemplateErrorKind::UnclosedBrace => {
+                write!(
+                    f,
+                    "this `{{` is never closed.  Write `{{{{` for a literal `{{`"
+                )
+            }
+            TemplateErrorKind::UnmatchedClosingBrace => {
+                write!(
+                    f,
+                    "this `}}` was never opened.  Write `}}}}` for a literal `}}`"
+                )
+            }
+            TemplateErrorKind::UnexpectedElse => write!(f, "`{{else}}` outside of an `{{if}}`"),
+            TemplateErrorKind::UnexpectedEnd => write!(f, "`{{end}}` without an `{{if}}`"),
+            TemplateErrorKind::UnclosedIf(ref condition) => {
+                write!(f, "`{{if {}}}` has no `{{end}}`", condition)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Returns the known placeholder that `name` was most likely a typo of, if any
+fn get_similar_placeholder(name: &str) -> Option<&'static str> {
+    let normalise = |name: &str| name.to_lowercase().replace(['_', '-', ' '], "");
+    let name = normalise(name);
+
+    PLACEHOLDERS
+        .iter()
+        .map(|(placeholder, _)| *placeholder)
+        .find(|placeholder| {
+            let placeholder = normalise(placeholder);
+            !name.is_empty() && (placeholder.starts_with(&name) || name.starts_with(&placeholder))
+        })
+}
+
+/// A part of a template
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Placeholder {
+        name: String,
+        format: Option<String>,
+    },
+    If {
+        condition: String,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// An `{if}` whose `{end}` has not been read yet
+struct OpenIf {
+    condition: String,
+    negated: bool,
+    then: Vec<Node>,
+    otherwise: Vec<Node>,
+    in_else: bool,
+    line: usize,
+    column: usize,
+}
+
+/// struct PreambleTemplate
+/// A template for the preamble of new entries, read from `preamble.txt` in the config directory
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PreambleTemplate {
+    nodes: Vec<Node>,
+}
+
+impl PreambleTemplate {
+    /// Reads a template, checking that every placeholder and condition in it is known
+    pub(crate) fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut stack: Vec<OpenIf> = vec![];
+        let mut nodes: Vec<Node> = vec![];
+        let mut text = String::new();
+
+        let (mut line, mut column) = (1, 1);
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let (tag_line, tag_column) = (line, column);
+            let error = |kind: TemplateErrorKind| TemplateError {
+                line: tag_line,
+                column: tag_column,
+                kind,
+            };
+
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    column += 1;
+                    text.push('{');
+                    continue;
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    column += 1;
+                    text.push('}');
+                    continue;
+                }
+                '}' => return Err(error(TemplateErrorKind::UnmatchedClosingBrace)),
+                '{' => (),
+                _ => {
+                    text.push(c);
+                    continue;
+                }
+            }
+
+            // Tags cannot span lines
+            let mut tag = String::new();
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some('\n') | None => return Err(error(TemplateErrorKind::UnclosedBrace)),
+                    Some(c) => tag.push(c),
+                }
+            }
+            column += tag.chars().count() + 1;
+
+            if !text.is_empty() {
+                get_current_branch(&mut nodes, &mut stack)
+                    .push(Node::Text(std::mem::take(&mut text)));
+            }
+
+            let tag = tag.trim();
+            let words: Vec<&str> = tag.split_whitespace().collect();
+            match words.as_slice() {
+                ["if", condition] | ["if", "not", condition] => {
+                    if !is_known_condition(condition) {
+                        return Err(error(TemplateErrorKind::UnknownCondition(
+                            condition.to_string(),
+                        )));
+                    }
+
+                    stack.push(OpenIf {
+                        condition: condition.to_string(),
+                        negated: words.len() == 3,
+                        then: vec![],
+                        otherwise: vec![],
+                        in_else: false,
+                        line: tag_line,
+                        column: tag_column,
+                    });
+                }
+                ["else"] => match stack.last_mut() {
+                    Some(open_if) if !open_if.in_else => open_if.in_else = true,
+                    _ => return Err(error(TemplateErrorKind::UnexpectedElse)),
+                },
+                ["end"] => {
+                    let open_if = stack.pop().ok_or(error(TemplateErrorKind::UnexpectedEnd))?;
+
+                    get_current_branch(&mut nodes, &mut stack).push(Node::If {
+                        condition: open_if.condition,
+                        negated: open_if.negated,
+                        then: open_if.then,
+                        otherwise: open_if.otherwise,
+                    });
+                }
+                _ => {
+                    let (name, format) = match tag.split_once(':') {
+                        Some((name, format)) => (name.trim(), Some(format.to_string())),
+                        None => (tag, None),
+                    };
+
+                    if !PLACEHOLDERS.iter().any(|(known, _)| *known == name) {
+                        return Err(error(TemplateErrorKind::UnknownPlaceholder(
+                            name.to_string(),
+                        )));
+                    }
+                    if let Some(ref format) = format {
+                        if name != "date" {
+                            return Err(error(TemplateErrorKind::UnexpectedFormat(
+                                name.to_string(),
+                            )));
+                        }
+                        if chrono::format::StrftimeItems::new(format)
+                            .any(|item| item == chrono::format::Item::Error)
+                        {
+                            return Err(error(TemplateErrorKind::InvalidDateFormat(
+                                format.clone(),
+                            )));
+                        }
+                    }
+
+                    get_current_branch(&mut nodes, &mut stack).push(Node::Placeholder {
+                        name: name.to_string(),
+                        format,
+                    });
+                }
+            }
+        }
+
+        if let Some(open_if) = stack.pop() {
+            return Err(TemplateError {
+                line: open_if.line,
+                column: open_if.column,
+                kind: TemplateErrorKind::UnclosedIf(open_if.condition),
+            });
+        }
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+
+        Ok(PreambleTemplate { nodes })
+    }
+
+    /// Writes the preamble of `entry` with this template.  The entry's body is not included.
+    pub(crate) fn render(&self, entry: &core::entry::Entry) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, entry, &mut output);
+
+        output
+    }
+}
+
+/// Returns the nodes that the next part of the template belongs to, i.e. those of the innermost `{if}`
+fn get_current_branch<'a>(nodes: &'a mut Vec<Node>, stack: &'a mut [OpenIf]) -> &'a mut Vec<Node> {
+    match stack.last_mut() {
+        Some(open_if) if open_if.in_else => &mut open_if.otherwise,
+        Some(open_if) => &mut open_if.then,
+        None => nodes,
+    }
+}
+
+fn is_known_condition(name: &str) -> bool {
+    CONDITIONS.contains(&name) || PLACEHOLDERS.iter().any(|(known, _)| *known == name)
+}
+
+fn render_nodes(nodes: &[Node], entry: &core::entry::Entry, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Placeholder { name, format } => output.push_str(
+                &get_value(name, format.as_deref(), entry).unwrap_or(NOT_AVAILABLE.to_string()),
+            ),
+            Node::If {
+                condition,
+                negated,
+                then,
+                otherwise,
+            } => {
+                if is_condition_met(condition, entry) != *negated {
+                    render_nodes(then, entry, output);
+                } else {
+                    render_nodes(otherwise, entry, output);
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether the entry was written between sunrise and sunset, if known
+fn is_day(entry: &core::entry::Entry) -> Option<bool> {
+    let weather = entry.weather.as_ref()?;
+    let sunrise = chrono::NaiveTime::parse_from_str(&weather.sunrise, "%H:%M").ok()?;
+    let sunset = chrono::NaiveTime::parse_from_str(&weather.sunset, "%H:%M").ok()?;
+    let time = entry.datetime.time();
+
+    Some(sunrise <= time && time < sunset)
+}
+
+fn is_condition_met(condition: &str, entry: &core::entry::Entry) -> bool {
+    match condition {
+        "day" => is_day(entry) == Some(true),
+        "night" => is_day(entry) == Some(false),
+        _ => {
+            get_value(condition, None, entry).is_some_and(|value| !value.is_empty() && value != "0")
+        }
+    }
+}
+
+/// Returns the value of a placeholder for `entry`, or `None` if it is not available
+fn get_value(name: &str, format: Option<&str>, entry: &core::entry::Entry) -> Option<String> {
+    let location = entry.location.as_deref();
+    let weather = entry.weather.as_ref();
+
+    let value = match name {
+        "date" => {
+            let format = format.map_or(
+                format!(
+                    "{} {} (%:z)",
+                    core::entry::DATE_FORMAT,
+                    entry.timezone_abbreviation
+                ),
+                // `%Z` of a fixed offset would be the offset itself
+                |format| format.replace("%Z", &entry.timezone_abbreviation),
+            );
+            entry.datetime.format(&format).to_string()
+        }
+        "timezone" => entry.timezone_abbreviation.clone(),
+        "offset" => entry.datetime.format("%:z").to_string(),
+        "location" => location?.to_string(),
+        "location_name" => core::str_man::split_location(location?).to_string(),
+        "location_details" => location?.rsplit_once(',')?.0.trim().to_string(),
+        "temperature" => weather?.temperature.to_string(),
+        "apparent_temperature" => weather?.apparent_temperature.to_string(),
+        "weather_code" => weather?.weather_code.to_string(),
+        "weather_description" => {
+            core::weather::get_weather_description(weather?.weather_code).to_string()
+        }
+        "rain" => weather?.rain.to_string(),
+        "windspeed" => weather?.windspeed.to_string(),
+        "winddirection" => weather?.winddirection.to_string(),
+        "wind_direction" => core::helper::get_direction(weather?.winddirection),
+        "pressure" => weather?.pressure.to_string(),
+        "humidity" => weather?.humidity.to_string(),
+        "visibility" => weather?.visibility?.to_string(),
+        "visibility_km" => (weather?.visibility? / 1000.0).to_string(),
+        "uv_index" => weather?.uv_index?.to_string(),
+        "sunrise" => weather?.sunrise.clone(),
+        "sunset" => weather?.sunset.clone(),
+        "template" => entry
+            .fields
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(core::entry::TEMPLATE_FIELD))?
+            .1
+            .clone(),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Picks the prompt for a date.  The same date always gets the same prompt, and
+/// consecutive dates go through the prompts in order.
+pub(crate) fn get_prompt_of_the_day(prompts: &[String], date: chrono::NaiveDate) -> Option<&str> {
+    if prompts.is_empty() {
+        return None;
+    }
+
+    let day = chrono::Datelike::num_days_from_ce(&date).unsigned_abs() as usize;
+
+    Some(&prompts[day % prompts.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An entry written at 23:26, i.e. at night, on a day with some rain
+    fn sample_entry() -> core::entry::Entry {
+        let mut entry = core::entry::tests::sample_entry(core::entry::EntryFormat::Plain);
+        entry.weather.as_mut().unwrap().rain = 0.5;
+
+        entry
+    }
+
+    fn render(template: &str, entry: &core::entry::Entry) -> String {
+        PreambleTemplate::parse(template).unwrap().render(entry)
+    }
+
+    fn parse_error(template: &str) -> TemplateError {
+        PreambleTemplate::parse(template).unwrap_err()
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let template = "{location_name}: {temperature} C, {wind_direction}, UV {uv_index}";
+
+        let mut entry = sample_entry();
+        assert_eq!(render(template, &entry), "Cebu City: 26 C, SSW, UV 8.8");
+
+        entry.weather.as_mut().unwrap().uv_index = None;
+        assert_eq!(render(template, &entry), "Cebu City: 26 C, SSW, UV n/a");
+    }
+
+    #[test]
+    fn renders_nested_ifs() {
+        let template = "{if rain}Rain{if night} at night{else} by day{end}.{else}Dry.{end}";
+
+        let mut entry = sample_entry();
+        assert_eq!(render(template, &entry), "Rain at night.");
+
+        entry.datetime -= chrono::Duration::hours(12);
+        assert_eq!(render(template, &entry), "Rain by day.");
+
+        entry.weather.as_mut().unwrap().rain = 0.0;
+        assert_eq!(render(template, &entry), "Dry.");
+        assert_eq!(render("{if not rain}Dry.{end}", &entry), "Dry.");
+    }
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        assert_eq!(
+            render("{{date}} is {{{date:%Y}}}, }} {{", &sample_entry()),
+            "{date} is {2023}, } {"
+        );
+    }
+
+    #[test]
+    fn date_takes_a_format() {
+        let entry = sample_entry();
+
+        assert_eq!(render("{date:%H:%M}", &entry), "23:26");
+        assert_eq!(render("{date:%H:%M %Z}", &entry), "23:26 PST");
+        assert_eq!(
+            render("{date}", &entry),
+            "Tue, 2023 Aug 22 23:26:41 PST (+08:00)"
+        );
+    }
+
+    #[test]
+    fn unmatched_end_is_an_error() {
+        assert_eq!(
+            parse_error("DATE: {date}\n  {end}"),
+            TemplateError {
+                line: 2,
+                column: 3,
+                kind: TemplateErrorKind::UnexpectedEnd,
+            }
+        );
+        assert_eq!(
+            parse_error("{if rain}{else}{else}{end}").kind,
+            TemplateErrorKind::UnexpectedElse
+        );
+    }
+
+    #[test]
+    fn unclosed_if_is_reported_where_it_opens() {
+        assert_eq!(
+            parse_error("Weather:\n{if rain}Rain\n{if night}!{end}\n"),
+            TemplateError {
+                line: 2,
+                column: 1,
+                kind: TemplateErrorKind::UnclosedIf("rain".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn errors_have_the_column_they_happened_at() {
+        // Columns count characters, not bytes, and continue after each placeholder
+        assert_eq!(
+            parse_error("{temperature}°C {temprature}"),
+            TemplateError {
+                line: 1,
+                column: 17,
+                kind: TemplateErrorKind::UnknownPlaceholder("temprature".to_string()),
+            }
+        );
+        assert_eq!(parse_error("{{ok}} }").column, 8);
+        assert_eq!(
+            parse_error("Rain: {rain"),
+            TemplateError {
+                line: 1,
+                column: 7,
+                kind: TemplateErrorKind::UnclosedBrace,
+            }
+        );
+        assert_eq!(
+            parse_error("{rain:%H}").kind,
+            TemplateErrorKind::UnexpectedFormat("rain".to_string())
+        );
+        assert_eq!(
+            parse_error("{date:%Q}").kind,
+            TemplateErrorKind::InvalidDateFormat("%Q".to_string())
+        );
+        assert_eq!(
+            parse_error("{if sunny}{end}").kind,
+            TemplateErrorKind::UnknownCondition("sunny".to_string())
+        );
+    }
+}
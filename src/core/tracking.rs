@@ -0,0 +1,295 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains the mood, energy and sleep values that `new` can ask for, and their trends
+//!
+//! The values are kept in the entry's header as `MOOD: 4`, `ENERGY: 3` and
+//! `SLEEP: 7.5`.  Mood and energy are from 1 to 5, and sleep is in hours.
+
+use crate::core;
+
+/// Header fields of the tracked values
+pub(crate) const MOOD_FIELD: &str = "MOOD";
+pub(crate) const ENERGY_FIELD: &str = "ENERGY";
+pub(crate) const SLEEP_FIELD: &str = "SLEEP";
+
+/// Characters of a sparkline, from the lowest value to the highest
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// struct Tracking
+/// The tracked values of an entry.  Each one is optional, as the user may skip it.
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Tracking {
+    pub(crate) mood: Option<f64>,
+    pub(crate) energy: Option<f64>,
+    /// In hours
+    pub(crate) sleep: Option<f64>,
+}
+
+impl Tracking {
+    /// Reads the tracked values from an entry's header
+    pub(crate) fn from_contents(contents: &str) -> Self {
+        let get = |key: &str| {
+            core::entry::get_header_field(contents, key).and_then(|value| value.parse().ok())
+        };
+
+        Tracking {
+            mood: get(MOOD_FIELD),
+            energy: get(ENERGY_FIELD),
+            sleep: get(SLEEP_FIELD),
+        }
+    }
+
+    /// Returns the header fields to write for the values that were given
+    pub(crate) fn to_fields(&self) -> Vec<(String, String)> {
+        Metric::ALL
+            .iter()
+            .filter_map(|metric| {
+                let value = metric.get(self)?;
+                Some((metric.get_field().to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        Metric::ALL.iter().all(|metric| metric.get(self).is_none())
+    }
+}
+
+/// enum Metric
+/// One of the tracked values
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Metric {
+    Mood,
+    Energy,
+    Sleep,
+}
+
+impl Metric {
+    pub(crate) const ALL: [Metric; 3] = [Metric::Mood, Metric::Energy, Metric::Sleep];
+
+    pub(crate) fn get(&self, tracking: &Tracking) -> Option<f64> {
+        match self {
+            Metric::Mood => tracking.mood,
+            Metric::Energy => tracking.energy,
+            Metric::Sleep => tracking.sleep,
+        }
+    }
+
+    pub(crate) fn get_field(&self) -> &'static str {
+        match self {
+            Metric::Mood => MOOD_FIELD,
+            Metric::Energy => ENERGY_FIELD,
+            Metric::Sleep => SLEEP_FIELD,
+        }
+    }
+
+    /// Returns the lowest and highest value the metric can have, if it is bounded
+    pub(crate) fn get_range(&self) -> Option<(f64, f64)> {
+        match self {
+            Metric::Mood | Metric::Energy => Some((1.0, 5.0)),
+            Metric::Sleep => None,
+        }
+    }
+}
+
+/// enum Period
+/// The length of the periods that `trends` averages over, and that `review` covers
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Period {
+    Week,
+    Month,
+    Year,
+}
+
+impl Period {
+    /// Returns the name of the period a date falls in, e.g. `2024-W26`, `2024-06` or `2024`
+    pub(crate) fn get_name(&self, date: chrono::NaiveDate) -> String {
+        match self {
+            Period::Week => {
+                let week = chrono::Datelike::iso_week(&date);
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => date.format("%Y-%m").to_string(),
+            Period::Year => date.format("%Y").to_string(),
+        }
+    }
+
+    /// Returns the first and last day of the period a date falls in.  Weeks start on Monday.
+    pub(crate) fn get_range(
+        &self,
+        date: chrono::NaiveDate,
+    ) -> (chrono::NaiveDate, chrono::NaiveDate) {
+        use chrono::Datelike;
+
+        let first_day = match self {
+            Period::Week => date - chrono::Days::new(date.weekday().num_days_from_monday() as u64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+            Period::Year => date.with_ordinal(1).unwrap_or(date),
+        };
+        let next_first_day = match self {
+            Period::Week => first_day.checked_add_days(chrono::Days::new(7)),
+            Period::Month => first_day.checked_add_months(chrono::Months::new(1)),
+            Period::Year => first_day.checked_add_months(chrono::Months::new(12)),
+        };
+
+        (
+            first_day,
+            next_first_day
+                .and_then(|day| day.pred_opt())
+                .unwrap_or(chrono::NaiveDate::MAX),
+        )
+    }
+}
+
+/// struct PeriodAverages
+/// The average of every metric in a period
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PeriodAverages {
+    pub(crate) name: String,
+    /// Number of entries in the period with at least one tracked value
+    pub(crate) entry_count: usize,
+    /// In the order of `Metric::ALL`
+    pub(crate) averages: [Option<f64>; 3],
+}
+
+/// Returns the averages of each period that has tracked values, oldest first
+pub(crate) fn get_period_averages(
+    entries: &[&core::index::IndexedEntry],
+    period: Period,
+) -> Vec<PeriodAverages> {
+    let mut periods: std::collections::BTreeMap<String, Vec<&Tracking>> =
+        std::collections::BTreeMap::new();
+
+    for entry in entries.iter().filter(|entry| !entry.tracking.is_empty()) {
+        periods
+            .entry(period.get_name(entry.datetime.date()))
+            .or_default()
+            .push(&entry.tracking);
+    }
+
+    periods
+        .into_iter()
+        .map(|(name, trackings)| PeriodAverages {
+            name,
+            entry_count: trackings.len(),
+            averages: Metric::ALL.map(|metric| {
+                get_average(trackings.iter().filter_map(|tracking| metric.get(tracking)))
+            }),
+        })
+        .collect()
+}
+
+fn get_average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Draws `values` as a sparkline, with a space for each missing value.
+///
+/// Without a `range`, the lowest and highest of `values` are used.
+pub(crate) fn get_sparkline(values: &[Option<f64>], range: Option<(f64, f64)>) -> String {
+    let present = values.iter().flatten().copied();
+    let (low, high) = range.unwrap_or_else(|| {
+        present.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+            (low.min(value), high.max(value))
+        })
+    });
+
+    values
+        .iter()
+        .map(|value| match value {
+            Some(value) if high > low => {
+                let position = ((value - low) / (high - low)).clamp(0.0, 1.0);
+                SPARKS[(position * (SPARKS.len() - 1) as f64).round() as usize]
+            }
+            Some(_) => SPARKS[SPARKS.len() / 2],
+            None => ' ',
+        })
+        .collect()
+}
+
+/// enum WeatherVariable
+/// A part of the weather that the tracked values can be compared with
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum WeatherVariable {
+    Temperature,
+    Rain,
+    Humidity,
+}
+
+impl WeatherVariable {
+    pub(crate) const ALL: [WeatherVariable; 3] = [
+        WeatherVariable::Temperature,
+        WeatherVariable::Rain,
+        WeatherVariable::Humidity,
+    ];
+
+    pub(crate) fn get(&self, weather: &core::weather::Weather) -> f64 {
+        match self {
+            WeatherVariable::Temperature => weather.temperature,
+            WeatherVariable::Rain => weather.rain,
+            WeatherVariable::Humidity => weather.humidity,
+        }
+    }
+
+    pub(crate) fn get_name(&self) -> &'static str {
+        match self {
+            WeatherVariable::Temperature => "TEMPERATURE",
+            WeatherVariable::Rain => "RAIN",
+            WeatherVariable::Humidity => "HUMIDITY",
+        }
+    }
+}
+
+/// Returns Pearson's correlation coefficient of `metric` and `variable` over the
+/// entries that have both, with the number of those entries.
+///
+/// The coefficient is `None` with fewer than three such entries, or if either never changes.
+pub(crate) fn get_correlation(
+    entries: &[&core::index::IndexedEntry],
+    metric: Metric,
+    variable: WeatherVariable,
+) -> (Option<f64>, usize) {
+    let pairs: Vec<(f64, f64)> = entries
+        .iter()
+        .filter_map(|entry| {
+            Some((
+                metric.get(&entry.tracking)?,
+                variable.get(entry.weather.as_ref()?),
+            ))
+        })
+        .collect();
+
+    if pairs.len() < 3 {
+        return (None, pairs.len());
+    }
+
+    let count = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / count;
+
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs.iter() {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x).powi(2);
+        variance_y += (y - mean_y).powi(2);
+    }
+
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return (None, pairs.len());
+    }
+
+    (
+        Some(covariance / (variance_x * variance_y).sqrt()),
+        pairs.len(),
+    )
+}
@@ -0,0 +1,144 @@
+/*******************************************************************************
+ * Copyright (c) 2023, 2024  Noah Dominic Miranda Silvio.  All rights reserved
+ * Licensed under the EUPL v1.2
+ ******************************************************************************/
+
+//! Contains functions for deleting entries into, and restoring them from, the trash
+//!
+//! The trash lives in `.trash/` inside the data directory and mirrors the
+//! `YYYY/MM/DD.HH-mm.txt` layout of the journal, so that restoring an entry
+//! is just moving it back.  When each entry was deleted is recorded in
+//! `.trash/.deleted_at.json`.
+
+use std::collections::BTreeMap;
+
+use crate::core;
+
+/// struct TrashedEntry
+/// An entry in the trash
+////////////////////////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone)]
+pub(crate) struct TrashedEntry {
+    pub(crate) entry: core::file::EntryFile,
+    /// Where the entry was before it was deleted
+    pub(crate) original_path: std::path::PathBuf,
+    /// `None` if the entry was put in the trash by hand
+    pub(crate) deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Returns the path of an entry relative to its root directory, with forward slashes
+fn get_relative_key(path: &std::path::Path, root: &std::path::Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn read_deletion_log(
+) -> Result<BTreeMap<String, chrono::DateTime<chrono::Utc>>, core::file::FileError> {
+    let log = core::file::read_journal_file_if_exists(&core::file::get_deletion_log_file_path()?)?
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    Ok(log)
+}
+
+fn write_deletion_log(
+    log: &BTreeMap<String, chrono::DateTime<chrono::Utc>>,
+) -> Result<(), core::file::FileError> {
+    let contents = serde_json::to_vec_pretty(log)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    core::file::write_journal_file(&core::file::get_deletion_log_file_path()?, &contents)?;
+
+    Ok(())
+}
+
+/// Moves an entry into the trash
+pub(crate) fn move_to_trash(entry: &core::file::EntryFile) -> Result<(), core::file::FileError> {
+    let data_dir = core::file::get_data_dir_path()?;
+    let trash_dir = core::file::get_trash_dir_path()?;
+
+    let key = get_relative_key(&entry.path, &data_dir);
+    let trashed_path = trash_dir.join(&key);
+
+    if trashed_path.exists() {
+        return Err(core::file::FileError::from(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "An entry with the same date and time is already in the trash.  \
+            Restore it or empty the trash first.",
+        )));
+    }
+
+    if let Some(parent) = trashed_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&entry.path, &trashed_path)?;
+
+    let mut log = read_deletion_log()?;
+    log.insert(key, chrono::Utc::now());
+    write_deletion_log(&log)?;
+
+    Ok(())
+}
+
+/// Returns every entry in the trash, sorted from oldest to newest by their own date
+pub(crate) fn get_trashed_entries() -> Result<Vec<TrashedEntry>, core::file::FileError> {
+    let data_dir = core::file::get_data_dir_path()?;
+    let trash_dir = core::file::get_trash_dir_path()?;
+
+    if !trash_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let log = read_deletion_log()?;
+
+    let trashed_entries = core::file::get_entries_in_dir(&trash_dir)?
+        .into_iter()
+        .map(|entry| {
+            let key = get_relative_key(&entry.path, &trash_dir);
+            TrashedEntry {
+                original_path: data_dir.join(&key),
+                deleted_at: log.get(&key).copied(),
+                entry,
+            }
+        })
+        .collect();
+
+    Ok(trashed_entries)
+}
+
+/// Moves an entry from the trash back to where it was
+pub(crate) fn restore(trashed: &TrashedEntry) -> Result<(), core::file::FileError> {
+    if trashed.original_path.exists() {
+        return Err(core::file::FileError::from(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "There is already an entry with the same date and time in the journal.",
+        )));
+    }
+
+    if let Some(parent) = trashed.original_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&trashed.entry.path, &trashed.original_path)?;
+
+    forget(trashed)
+}
+
+/// Deletes an entry in the trash for good
+pub(crate) fn remove_permanently(trashed: &TrashedEntry) -> Result<(), core::file::FileError> {
+    std::fs::remove_file(&trashed.entry.path)?;
+
+    forget(trashed)
+}
+
+/// Removes an entry that has left the trash from the deletion log
+fn forget(trashed: &TrashedEntry) -> Result<(), core::file::FileError> {
+    let trash_dir = core::file::get_trash_dir_path()?;
+
+    let mut log = read_deletion_log()?;
+    log.remove(&get_relative_key(&trashed.entry.path, &trash_dir));
+    write_deletion_log(&log)?;
+
+    Ok(())
+}
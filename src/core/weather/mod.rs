@@ -6,7 +6,7 @@
 
 pub(crate) mod query;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub(crate) struct Weather {
     pub(crate) temperature: f64,
     pub(crate) apparent_temperature: f64,
@@ -16,12 +16,80 @@ pub(crate) struct Weather {
     pub(crate) winddirection: f64,
     pub(crate) pressure: f64,
     pub(crate) humidity: f64,
-    pub(crate) visibility: f64,
-    pub(crate) uv_index: f64,
+    /// Not available for past dates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) visibility: Option<f64>,
+    /// Not available for past dates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) uv_index: Option<f64>,
     pub(crate) sunrise: String,
     pub(crate) sunset: String,
 }
 
+// Yes, I'm aware this isn't a data structure, but it IS a getter to a data structure
+pub(crate) fn get_weather_map() -> std::collections::HashMap<usize, &'static str> {
+    std::collections::HashMap::from([
+        (0, "Clear skies"),
+        (1, "Mainly clear skies"),
+        (2, "Partly cloudy skies"),
+        (3, "Overcast skies"),
+        (45, "Fog"),
+        (48, "Fog"),
+        (51, "Light drizzle"),
+        (53, "Moderate drizzle"),
+        (55, "Heavy drizzle"),
+        (56, "Light drizzle, freezing"),
+        (57, "Moderate or heavy drizzle, freezing"),
+        (61, "Light rain"),
+        (63, "Moderate rain"),
+        (65, "Heavy rain"),
+        (66, "Light rain, freezing"),
+        (67, "Moderate or heavy rain, freezing"),
+        (71, "Snow fall: Slight intensity"),
+        (73, "Snow fall: Moderate intensity"),
+        (75, "Snow fall: Heavy intensity"),
+        (77, "Snow grains"),
+        (80, "Light rain showers"),
+        (81, "Moderate rain showers"),
+        (82, "Violent rain showers"),
+        (85, "Snow showers: Slight intensity"),
+        (86, "Snow showers: Heavy intensity"),
+        (95, "Thunderstorm: Slight or moderate"),
+        (96, "Thunderstorm with slight hail"),
+        (99, "Thunderstorm with heavy hail"),
+    ])
+}
+
+/// Returns the human-readable description of a WMO weather code
+pub(crate) fn get_weather_description(weather_code: usize) -> &'static str {
+    get_weather_map()
+        .get(&weather_code)
+        .copied()
+        .unwrap_or(UNKNOWN_WEATHER_DESCRIPTION)
+}
+
+/// Returns the WMO weather code for a description written by `get_weather_description`.
+///
+/// Some codes share a description, in which case the lowest one is returned.
+pub(crate) fn get_weather_code(description: &str) -> Option<usize> {
+    if description == UNKNOWN_WEATHER_DESCRIPTION {
+        return Some(UNKNOWN_WEATHER_CODE);
+    }
+
+    get_weather_map()
+        .into_iter()
+        .filter(|(_, known_description)| known_description.eq_ignore_ascii_case(description))
+        .map(|(code, _)| code)
+        .min()
+}
+
+/// Description of weather codes missing from `get_weather_map`
+const UNKNOWN_WEATHER_DESCRIPTION: &str = "Unknown conditions";
+
+/// Stand-in code for entries whose weather was described as unknown.  Not a WMO code,
+/// and small enough to be written as a TOML integer in front matter
+const UNKNOWN_WEATHER_CODE: usize = 9999;
+
 #[derive(Debug, serde::Deserialize)]
 struct DailyWeather {
     sunrise: Vec<String>,
@@ -42,8 +110,47 @@ struct HourlyWeather {
     weathercode: Vec<usize>,
 }
 
+/// What Open Meteo answers with instead of the weather, e.g. for a date it has no data for
+#[derive(Debug, serde::Deserialize)]
+struct ErrorResult {
+    error: bool,
+}
+
+/// Returns whether Open Meteo answered with an error instead of the weather
+fn is_error_result(api_response_bytes: &[u8]) -> bool {
+    serde_json::from_slice::<ErrorResult>(api_response_bytes).is_ok_and(|result| result.error)
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct WeatherResult {
     hourly: HourlyWeather,
     daily: DailyWeather,
-}
\ No newline at end of file
+}
+
+/// Hourly weather from Open Meteo's historical archive.
+///
+/// The archive has fewer variables than the forecast, uses newer names for
+/// them, and leaves the most recent days empty until their data is in.
+#[derive(Debug, serde::Deserialize)]
+struct ArchiveHourlyWeather {
+    temperature_2m: Vec<Option<f64>>,
+    relative_humidity_2m: Vec<Option<f64>>,
+    apparent_temperature: Vec<Option<f64>>,
+    rain: Vec<Option<f64>>,
+    pressure_msl: Vec<Option<f64>>,
+    wind_speed_100m: Vec<Option<f64>>,
+    wind_direction_100m: Vec<Option<f64>>,
+    weather_code: Vec<Option<usize>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ArchiveDailyWeather {
+    sunrise: Vec<String>,
+    sunset: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ArchiveWeatherResult {
+    hourly: ArchiveHourlyWeather,
+    daily: ArchiveDailyWeather,
+}
@@ -6,12 +6,146 @@
 
 use crate as journey2;
 
+/// Gets the weather at a location at any moment, past or future.
+///
+/// Past dates are looked up in Open Meteo's historical archive.  As the archive
+/// lags behind by a few days, the forecast (which also keeps the recent past) is
+/// used for past dates that are not in the archive yet.  Today and the next
+/// days are looked up in the forecast.
+///
+/// # Returns
+///
+/// `None` if there is no weather for that moment, e.g. as it is beyond the
+/// forecast, or in a gap in the archive.
+pub(crate) fn get_weather_at_location_and_time(
+    date: &chrono::DateTime<chrono_tz::Tz>,
+    latitude: &str,
+    longitude: &str,
+    timezone: &str,
+) -> Result<Option<journey2::core::weather::Weather>, Box<dyn std::error::Error>> {
+    let today = chrono::Utc::now()
+        .with_timezone(&date.timezone())
+        .date_naive();
+
+    if date.date_naive() < today {
+        if let Some(weather) = get_historical_weather_at_location_and_time(
+            &date.to_string(),
+            latitude,
+            longitude,
+            timezone,
+        )? {
+            return Ok(Some(weather));
+        }
+    }
+
+    get_current_weather_at_location_and_time(&date.to_string(), latitude, longitude, timezone)
+}
+
+/// Gets the weather at a past moment from Open Meteo's historical archive.
+///
+/// # Returns
+///
+/// `None` if the archive does not have data for that moment, e.g. as it is not in yet.
+pub(crate) fn get_historical_weather_at_location_and_time(
+    date: &str,
+    latitude: &str,
+    longitude: &str,
+    timezone: &str,
+) -> Result<Option<journey2::core::weather::Weather>, Box<dyn std::error::Error>> {
+    let (date_iso, hour) = journey2::core::chrono::preprocess_datetime_for_url(date);
+    let timezone_url_ready = journey2::core::chrono::preprocess_timezone_for_url(timezone);
+
+    // Calling the API now...
+    let url = format!(
+        "https://archive-api.open-meteo.com/v1/archive?\
+                                latitude={latitude}\
+                                &longitude={longitude}\
+                                &hourly=\
+                                    temperature_2m,\
+                                    relative_humidity_2m,\
+                                    apparent_temperature,\
+                                    rain,\
+                                    pressure_msl,\
+                                    wind_speed_100m,\
+                                    wind_direction_100m,\
+                                    weather_code\
+                                &daily=\
+                                    sunrise,\
+                                    sunset\
+                                &timezone={timezone_url_ready}\
+                                &start_date={date_iso}\
+                                &end_date={date_iso}"
+    );
+    let api_response_bytes = journey2::om_api::call_api(&url)?;
+    if journey2::core::weather::is_error_result(&api_response_bytes) {
+        return Ok(None);
+    }
+
+    // Deserialising the data received
+    let api_response_native: journey2::core::weather::ArchiveWeatherResult =
+        serde_json::from_slice(&api_response_bytes)?;
+
+    let hourly = api_response_native.hourly;
+    let at_hour = |values: &Vec<Option<f64>>| values.get(hour).copied().flatten();
+
+    let (
+        Some(temperature),
+        Some(apparent_temperature),
+        Some(weather_code),
+        Some(rain),
+        Some(windspeed),
+        Some(winddirection),
+        Some(pressure),
+        Some(humidity),
+    ) = (
+        at_hour(&hourly.temperature_2m),
+        at_hour(&hourly.apparent_temperature),
+        hourly.weather_code.get(hour).copied().flatten(),
+        at_hour(&hourly.rain),
+        at_hour(&hourly.wind_speed_100m),
+        at_hour(&hourly.wind_direction_100m),
+        at_hour(&hourly.pressure_msl),
+        at_hour(&hourly.relative_humidity_2m),
+    )
+    else {
+        return Ok(None);
+    };
+
+    let time_of = |values: &Vec<String>| {
+        values
+            .first()
+            .and_then(|value| value.split('T').next_back())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    Ok(Some(journey2::core::weather::Weather {
+        temperature,
+        apparent_temperature,
+        weather_code,
+        rain,
+        windspeed,
+        winddirection,
+        pressure,
+        humidity,
+        visibility: None,
+        uv_index: None,
+        sunrise: time_of(&api_response_native.daily.sunrise),
+        sunset: time_of(&api_response_native.daily.sunset),
+    }))
+}
+
+/// Gets the weather at a moment from Open Meteo's forecast.
+///
+/// # Returns
+///
+/// `None` if the forecast does not have data for that moment, e.g. as it is too far ahead.
 pub(crate) fn get_current_weather_at_location_and_time(
     date: &str,
     latitude: &str,
     longitude: &str,
     timezone: &str,
-) -> Result<journey2::core::weather::Weather, Box<dyn std::error::Error>> {
+) -> Result<Option<journey2::core::weather::Weather>, Box<dyn std::error::Error>> {
     let (current_date_iso, current_hour) = journey2::core::chrono::preprocess_datetime_for_url(date);
     let timezone_url_ready = journey2::core::chrono::preprocess_timezone_for_url(timezone);
 
@@ -39,31 +173,40 @@ pub(crate) fn get_current_weather_at_location_and_time(
                                 &end_date={current_date_iso}"
     );
     let api_response_bytes = journey2::om_api::call_api(&url)?;
+    if journey2::core::weather::is_error_result(&api_response_bytes) {
+        return Ok(None);
+    }
 
     // Deserialising the data received
     let api_response_native: journey2::core::weather::WeatherResult =
         serde_json::from_slice(&api_response_bytes)?;
 
-    Ok(journey2::core::weather::Weather {
-        temperature: api_response_native.hourly.temperature_2m[current_hour],
-        apparent_temperature: api_response_native.hourly.apparent_temperature[current_hour],
-        weather_code: api_response_native.hourly.weathercode[current_hour],
-        rain: api_response_native.hourly.rain[current_hour],
-        windspeed: api_response_native.hourly.windspeed_120m[current_hour],
-        winddirection: api_response_native.hourly.winddirection_120m[current_hour],
-        pressure: api_response_native.hourly.pressure_msl[current_hour],
-        humidity: api_response_native.hourly.relativehumidity_2m[current_hour],
-        visibility: api_response_native.hourly.visibility[current_hour],
-        uv_index: api_response_native.daily.uv_index_max[0],
-        sunrise: api_response_native.daily.sunrise[0]
+    let hourly = &api_response_native.hourly;
+    let daily = &api_response_native.daily;
+    if current_hour >= hourly.temperature_2m.len() || daily.sunrise.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(journey2::core::weather::Weather {
+        temperature: hourly.temperature_2m[current_hour],
+        apparent_temperature: hourly.apparent_temperature[current_hour],
+        weather_code: hourly.weathercode[current_hour],
+        rain: hourly.rain[current_hour],
+        windspeed: hourly.windspeed_120m[current_hour],
+        winddirection: hourly.winddirection_120m[current_hour],
+        pressure: hourly.pressure_msl[current_hour],
+        humidity: hourly.relativehumidity_2m[current_hour],
+        visibility: Some(hourly.visibility[current_hour]),
+        uv_index: Some(daily.uv_index_max[0]),
+        sunrise: daily.sunrise[0]
             .split("T")
             .last()
             .unwrap()
             .to_string(),
-        sunset: api_response_native.daily.sunset[0]
+        sunset: daily.sunset[0]
             .split("T")
             .last()
             .unwrap()
             .to_string(),
-    })
+    }))
 }
\ No newline at end of file